@@ -1,13 +1,26 @@
 use std::path::PathBuf;
 
 use anyhow::Result;
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 use crate::commands;
+use crate::error;
+use crate::output;
 
 #[derive(Parser, Debug)]
-#[command(name = "pc", version, about = "Parallel coding helper (git worktree)")]
+#[command(
+    name = "pc",
+    version,
+    about = "Parallel coding helper (git worktree)",
+    after_help = error::EXIT_CODES_HELP
+)]
 struct Cli {
+    /// Suppress informational output (errors and warnings still go to stderr)
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    /// Print diagnostics (such as how long the git checks took) to stderr
+    #[arg(short, long, global = true, conflicts_with = "quiet")]
+    verbose: bool,
     #[command(subcommand)]
     command: Commands,
 }
@@ -15,9 +28,17 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Create a git worktree + branch
-    New(NewArgs),
+    New(Box<NewArgs>),
     /// Remove a worktree (git worktree remove)
     Rm(RmArgs),
+    /// List agents recorded by `pc new`
+    List(ListArgs),
+    /// Print an agent's worktree path (for `cd "$(pc path <agent>)"`)
+    Path(PathArgs),
+    /// Print shell integration (`pca <agent>` to cd into a worktree), e.g. `eval "$(pc shell-init zsh)"`
+    ShellInit(ShellInitArgs),
+    /// Serve agent status as JSON (`/agents`, `/agents/<name>`, `/health`) for dashboards
+    Serve(ServeArgs),
     /// Backward-compatible alias (hidden)
     #[command(hide = true)]
     Agent(AgentArgs),
@@ -32,12 +53,16 @@ struct AgentArgs {
 #[derive(Subcommand, Debug)]
 enum AgentCommands {
     /// Create a git worktree + branch
-    New(NewArgs),
+    New(Box<NewArgs>),
     /// Remove a worktree (git worktree remove)
     Rm(RmArgs),
+    /// List agents recorded by `pc new`
+    List(ListArgs),
+    /// Print an agent's worktree path
+    Path(PathArgs),
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Clone)]
 pub(crate) struct NewArgs {
     /// Branch name to create/use (can include `/`, e.g. `feat/tui-templates`).
     /// If omitted (TTY only), a TUI selector will be shown.
@@ -45,6 +70,15 @@ pub(crate) struct NewArgs {
     /// Override the derived agent name (used for worktree directory and metadata lookup)
     #[arg(long = "agent-name")]
     pub(crate) agent_name: Option<String>,
+    /// Create N agents `<branch>-1` .. `<branch>-N`, all from the same base
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "branch_name",
+        conflicts_with_all = ["recreate", "idempotent", "companions"],
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub(crate) count: Option<u32>,
     /// Base branch/ref for the new worktree branch (default: current HEAD).
     /// Pass `--base` without a value to select interactively (TTY only).
     #[arg(long, num_args = 0..=1, default_missing_value = "__tui__")]
@@ -52,38 +86,210 @@ pub(crate) struct NewArgs {
     /// Select base branch with an interactive TUI (sorted by recent updates)
     #[arg(long)]
     pub(crate) select_base: bool,
-    /// Base directory to place worktrees
+    /// Do not set the new branch's upstream, even when the base is a remote-tracking branch
+    #[arg(long)]
+    pub(crate) no_branch_track: bool,
+    /// After creating the agent, push the branch with `git push -u <REMOTE> <branch>`
+    #[arg(long, value_name = "REMOTE")]
+    pub(crate) push_upstream: Option<String>,
+    /// Lock the new worktree with `git worktree lock` (optionally with a reason) so
+    /// `git worktree prune` leaves it alone; `pc rm` unlocks it first
+    #[arg(
+        long,
+        visible_alias = "worktree-lock",
+        value_name = "REASON",
+        num_args = 0..=1,
+        default_missing_value = "",
+        conflicts_with = "attach"
+    )]
+    pub(crate) lock: Option<String>,
+    /// Run `git fetch --prune` before resolving the base (the remote of `--base`, else all remotes)
     #[arg(long)]
+    pub(crate) fetch: bool,
+    /// Base directory to place worktrees
+    #[arg(long, value_parser = parse_path)]
     pub(crate) base_dir: Option<PathBuf>,
     /// Do not open VS Code in a new window
     #[arg(long)]
     pub(crate) no_open: bool,
+    /// Copy a local devcontainer template directory (must contain devcontainer.json)
+    /// into the new worktree's `.devcontainer`
+    #[arg(long, value_parser = parse_path)]
+    pub(crate) template_dir: Option<PathBuf>,
+    /// Plain worktree + metadata: ignore any configured devcontainer template
+    /// (`.pc.toml` or profile)
+    #[arg(long, conflicts_with_all = ["template_dir", "env_from_base"])]
+    pub(crate) no_devcontainer: bool,
+    /// Write the committed `.env.example` of the commit the new worktree starts at (the
+    /// base, or the tip of an existing branch; not the working tree) as `.env` in the new
+    /// worktree, unless it already has a `.env`
+    #[arg(long)]
+    pub(crate) env_from_base: bool,
+    /// Only check out this directory in the new worktree (repeatable; git sparse-checkout
+    /// in cone mode, files at the repo root are always included). Skips the full checkout,
+    /// which dominates creation time on large repos
+    #[arg(long = "sparse", value_name = "PATH")]
+    pub(crate) sparse: Vec<String>,
+    /// Also check out an existing BRANCH of another local repository as a worktree under
+    /// `.pc-companions/<repo-name>` in the new worktree (repeatable), so it is visible
+    /// inside the same devcontainer. Removed together with the agent
+    #[arg(long = "companion", value_name = "REPO#BRANCH", value_parser = parse_companion)]
+    pub(crate) companions: Vec<(PathBuf, String)>,
+    /// Attach a label to the agent (repeatable), stored in the agent metadata
+    #[arg(long = "label", value_name = "KEY=VALUE", value_parser = parse_label)]
+    pub(crate) labels: Vec<(String, String)>,
+    /// If the current worktree has uncommitted changes, snapshot them into a WIP commit
+    /// (without touching the current worktree) and start the new branch from it
+    #[arg(long, conflicts_with = "copy_wip")]
+    pub(crate) include_wip: bool,
+    /// If the current worktree has uncommitted changes, copy the changed/untracked files
+    /// into the new worktree (nothing is committed)
+    #[arg(long)]
+    pub(crate) copy_wip: bool,
+    /// Use the `[agent_profile.<NAME>]` options from `.pc.toml` (flags given here still win)
+    #[arg(long, value_name = "NAME")]
+    pub(crate) profile: Option<String>,
+    /// Save the options of this invocation as `[agent_profile.<NAME>]` in the repo's
+    /// `.pc.toml` once the agent has been created
+    #[arg(long, value_name = "NAME")]
+    pub(crate) save_profile: Option<String>,
+    /// Skip the agent-count and disk-space checks configured in `.pc.toml`
+    #[arg(long)]
+    pub(crate) no_preflight: bool,
+    /// Adopt the branch's existing worktree (e.g. one made with plain `git worktree add`):
+    /// record it as this agent and open it. Fails if the branch has no worktree
+    #[arg(long, conflicts_with_all = ["recreate", "idempotent", "count"])]
+    pub(crate) attach: bool,
+    /// Succeed quietly if the worktree already exists on this branch (fails if it is on another branch)
+    #[arg(long, conflicts_with = "recreate")]
+    pub(crate) idempotent: bool,
+    /// Remove an existing worktree for this agent/branch (keeping the branch) and create it again
+    #[arg(long)]
+    pub(crate) recreate: bool,
+    /// With --recreate: remove the existing worktree even if it has local changes
+    #[arg(long, requires = "recreate")]
+    pub(crate) force: bool,
 }
 
 #[derive(Args, Debug)]
 pub(crate) struct RmArgs {
-    /// Branch name (or agent name) to remove.
+    /// Branch name, agent name, or worktree path (e.g. `.`) to remove.
     /// If omitted (TTY only), a TUI selector will be shown.
     pub(crate) branch_name: Option<String>,
     /// Override the derived agent name (used for default worktree path and metadata lookup)
     #[arg(long = "agent-name")]
     pub(crate) agent_name: Option<String>,
     /// Base directory to place worktrees (for locating existing worktree dir)
-    #[arg(long)]
+    #[arg(long, value_parser = parse_path)]
     pub(crate) base_dir: Option<PathBuf>,
     /// Force removal (passes --force to git worktree remove)
     #[arg(long)]
     pub(crate) force: bool,
+    /// Also treat files matching this gitignore pattern as disposable (repeatable),
+    /// in addition to `rm_ignore` from `.pc.toml`
+    #[arg(long, value_name = "PATTERN")]
+    pub(crate) ignore: Vec<String>,
+}
+
+fn parse_label(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected KEY=VALUE, got {s:?}")),
+    }
+}
+
+fn parse_companion(s: &str) -> Result<(PathBuf, String), String> {
+    let Some((repo, branch)) = s
+        .rsplit_once('#')
+        .filter(|(r, b)| !r.is_empty() && !b.is_empty())
+    else {
+        return Err(format!("expected REPO#BRANCH, got {s:?}"));
+    };
+    if repo.contains("://") || repo.starts_with("git@") {
+        return Err(format!(
+            "only local repositories are supported as companions, got {repo:?}"
+        ));
+    }
+    Ok((parse_path(repo)?, branch.to_string()))
+}
+
+/// `~`, `~user` and `$VAR` expansion for path flags (they may arrive quoted).
+fn parse_path(s: &str) -> Result<PathBuf, String> {
+    pc_cli::paths::expand_path(s)
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct PathArgs {
+    /// Agent name or branch name
+    pub(crate) agent: String,
+    /// Base directory to place worktrees (for locating existing worktree dir)
+    #[arg(long, value_parser = parse_path)]
+    pub(crate) base_dir: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct ShellInitArgs {
+    pub(crate) shell: Shell,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct ServeArgs {
+    /// Unix socket to listen on (default: `pc/pc.sock` in the git common dir)
+    #[arg(long, value_parser = parse_path)]
+    pub(crate) socket: Option<PathBuf>,
+    /// Listen on 127.0.0.1:PORT instead of a Unix socket (0 picks a free port)
+    #[arg(long, conflicts_with = "socket")]
+    pub(crate) port: Option<u16>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub(crate) enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct ListArgs {
+    /// Only agents whose branch matches this glob (`*`, `?`), e.g. `feat/*`
+    #[arg(long)]
+    pub(crate) branch: Option<String>,
+    /// Only agents with this label (repeatable; all must match)
+    #[arg(long = "label", value_name = "KEY=VALUE", value_parser = parse_label)]
+    pub(crate) labels: Vec<(String, String)>,
+    /// Output layout: aligned columns, tab-separated fields, or a JSON array
+    #[arg(long, value_enum, default_value_t = ListFormat::Table)]
+    pub(crate) format: ListFormat,
+    /// Shorthand for `--format json`
+    #[arg(long, conflicts_with = "format")]
+    pub(crate) json: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ListFormat {
+    Table,
+    Plain,
+    Json,
 }
 
 pub(crate) fn run() -> Result<()> {
     let cli = Cli::parse();
+    output::set_quiet(cli.quiet);
+    output::set_verbose(cli.verbose);
     match cli.command {
-        Commands::New(args) => commands::agent::cmd_new(args),
+        Commands::New(args) => commands::agent::cmd_new(*args),
         Commands::Rm(args) => commands::agent::cmd_rm(args),
+        Commands::List(args) => commands::list::cmd_list(args),
+        Commands::Path(args) => commands::agent::cmd_path(args),
+        Commands::ShellInit(args) => commands::shell::cmd_shell_init(args),
+        Commands::Serve(args) => commands::serve::cmd_serve(args),
         Commands::Agent(args) => match args.command {
-            AgentCommands::New(a) => commands::agent::cmd_new(a),
+            AgentCommands::New(a) => commands::agent::cmd_new(*a),
             AgentCommands::Rm(a) => commands::agent::cmd_rm(a),
+            AgentCommands::List(a) => commands::list::cmd_list(a),
+            AgentCommands::Path(a) => commands::agent::cmd_path(a),
         },
     }
 }
@@ -1,44 +1,104 @@
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use anyhow::{anyhow, bail, Context, Result};
 use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
 
-use crate::cli::{NewArgs as AgentNewArgs, RmArgs as AgentRmArgs};
+use crate::cli::{NewArgs as AgentNewArgs, PathArgs, RmArgs as AgentRmArgs};
+use crate::config;
+use crate::devcontainer;
+use crate::error::{bail_git_state, bail_usage, PcError, ResultExt};
 use crate::exec;
 use crate::git;
+use crate::i18n::{tr, trf, Msg};
+use crate::interrupt;
 use crate::meta::{self, AgentMeta};
+use crate::output::{debug, info};
+use crate::preflight;
+use crate::report::{ConsoleReporter, Reporter};
 use crate::vscode;
 
-use pc_cli::agent_name::{derive_agent_name_from_branch, is_valid_agent_name};
+use pc_cli::agent_name::{derive_agent_name_from_branch, is_valid_agent_name, MAX_AGENT_NAME_LEN};
 
 pub(crate) fn cmd_new(args: AgentNewArgs) -> Result<()> {
+    new_agent(args, None)
+}
+
+/// `pc new`; `wip` is the uncommitted-changes decision when the caller already made it
+/// (`--count` decides once for all agents).
+fn new_agent(mut args: AgentNewArgs, wip: Option<(WipHandling, Vec<PathBuf>)>) -> Result<()> {
     exec::ensure_in_path("git")?;
 
-    if !git::has_commit()? {
-        bail!(
+    let repo_root = git::repo_root()?;
+    if !git::has_commit(&repo_root)? {
+        bail_git_state!(
             "This git repository has no commits yet (unborn HEAD). \
 Create an initial commit, then re-run `pc new ...`."
         );
     }
 
-    let base_ref = match resolve_base_ref(&args)? {
+    let repo_name = repo_root
+        .file_name()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| anyhow!("Failed to get repo name from path: {}", repo_root.display()))?
+        .to_string();
+
+    let repo_config = config::load_repo_config(&repo_root)?;
+    if let Some(name) = args.profile.as_deref() {
+        let Some(profile) = repo_config.agent_profiles.get(name) else {
+            bail_usage!(
+                "Unknown agent profile: {name} (define [agent_profile.{name}] in {})",
+                config::REPO_CONFIG_FILE
+            );
+        };
+        apply_profile(&mut args, profile);
+    }
+    // Built (and validated) now, written only once the agent exists.
+    let save_as = match args.save_profile.clone() {
+        Some(name) => Some((name, profile_from_args(&args)?)),
+        None => None,
+    };
+
+    if args.fetch {
+        let remote = match args.base.as_deref() {
+            Some(base) if base != "__tui__" => {
+                let remotes = git::remotes(&repo_root)?;
+                base.split_once('/')
+                    .map(|(r, _)| r)
+                    .filter(|r| remotes.iter().any(|known| known == r))
+                    .map(str::to_string)
+            }
+            _ => None,
+        };
+        git::fetch(&repo_root, remote.as_deref())?;
+    }
+
+    let base_ref = match resolve_base_ref(&repo_root, &args)? {
         Some(v) => v,
         None => {
-            println!("Cancelled.");
+            info!("{}", tr(Msg::Cancelled));
             return Ok(());
         }
     };
 
+    if let Some(count) = args.count {
+        cmd_new_numbered(&repo_root, args, base_ref, count)?;
+        if let Some((name, profile)) = save_as {
+            save_profile(&repo_root, &name, &profile);
+        }
+        return Ok(());
+    }
+
     let branch_name = match args.branch_name.clone() {
         Some(v) => v,
         None => {
             if args.base.is_some() || args.select_base {
                 prompt_new_branch_name(&base_ref)?
             } else {
-                match select_target_branch_tui()? {
+                match select_target_branch_tui(&repo_root)? {
                     Some(v) => v,
                     None => {
-                        println!("Cancelled.");
+                        info!("{}", tr(Msg::Cancelled));
                         return Ok(());
                     }
                 }
@@ -46,168 +106,382 @@ Create an initial commit, then re-run `pc new ...`."
         }
     };
 
-    let repo_root = git::repo_root()?;
-    let repo_name = repo_root
-        .file_name()
-        .and_then(|s| s.to_str())
-        .ok_or_else(|| anyhow!("Failed to get repo name from path: {}", repo_root.display()))?
-        .to_string();
-
-    let worktree_base_dir = resolve_worktree_base_dir(&repo_root, &repo_name, args.base_dir)?;
+    let worktree_base_dir = resolve_worktree_base_dir(
+        &repo_root,
+        &repo_name,
+        args.base_dir,
+        repo_config.base_dir.clone(),
+    )?;
+    let base_dir_existed = worktree_base_dir.exists();
     std::fs::create_dir_all(&worktree_base_dir)
         .with_context(|| format!("Failed to create base dir: {}", worktree_base_dir.display()))?;
+    if let Err(e) = ensure_base_dir_outside_repo(&repo_root, &worktree_base_dir) {
+        if !base_dir_existed {
+            let _ = std::fs::remove_dir(&worktree_base_dir);
+        }
+        return Err(e);
+    }
 
-    git::ensure_branch_name_valid(&branch_name)?;
+    git::ensure_branch_name_valid(&repo_root, &branch_name)?;
 
     let agent_name = match args.agent_name {
         Some(v) => {
             if !is_valid_agent_name(&v) {
-                bail!("agent-name must match: [A-Za-z0-9._-]+ (and cannot be '.' or '..')");
+                bail_usage!("agent-name must match: [A-Za-z0-9._-]+ (and cannot be '.' or '..')");
             }
             v
         }
-        None => derive_agent_name_from_branch(&branch_name)?,
+        None => derive_agent_name_from_branch(&branch_name).or_usage()?,
     };
 
-    if let Some(existing) = git::worktree_path_for_branch(&branch_name)? {
-        eprintln!(
-            "Warning: worktree for branch already exists. Opening: {}",
-            existing.display()
-        );
-        return reopen_existing_worktree(&branch_name, &agent_name, &existing, args.no_open);
-    }
-
-    let worktree_dir_raw = worktree_base_dir.join(&agent_name);
-    if worktree_dir_raw.exists() {
-        if let Some(entry) = git::worktree_entry_for_path(&worktree_dir_raw)? {
-            if let Some(existing_ref) = entry.branch.as_deref() {
-                let wanted_ref = format!("refs/heads/{branch_name}");
-                if existing_ref != wanted_ref {
-                    bail!(
-                        "Worktree path already exists for a different branch: {} (existing: {})",
-                        worktree_dir_raw.display(),
-                        existing_ref
-                            .strip_prefix("refs/heads/")
-                            .unwrap_or(existing_ref)
-                    );
-                }
+    let template_dir = if args.no_devcontainer {
+        None
+    } else {
+        args.template_dir
+            .or_else(|| repo_config.template_dir.clone())
+    };
+    if let Some(dir) = template_dir.as_deref() {
+        devcontainer::validate_template_dir(dir)?;
+    }
+    let companion_specs = resolve_companions(&args.companions)?;
+
+    let worktree_dir_raw = agent_worktree_dir(
+        &worktree_base_dir,
+        repo_config.worktree_path_template.as_deref(),
+        &repo_name,
+        &agent_name,
+        &branch_name,
+    )?;
+    let checks_started = Instant::now();
+    let worktrees = git::worktrees(&repo_root)?;
+    let wanted_ref = format!("refs/heads/{branch_name}");
+    let other_branch = |entry: &git::WorktreeEntry| {
+        entry
+            .branch
+            .as_deref()
+            .filter(|r| *r != wanted_ref)
+            .map(|r| r.strip_prefix("refs/heads/").unwrap_or(r).to_string())
+    };
+    let existing = if let Some(entry) = git::find_worktree_for_branch(&worktrees, &branch_name) {
+        Some((entry.path.clone(), "worktree for branch already exists"))
+    } else if worktree_dir_raw.exists() {
+        if let Some(existing_branch) =
+            git::find_worktree_for_path(&worktrees, &worktree_dir_raw).and_then(other_branch)
+        {
+            return Err(PcError::WorktreeExists {
+                what: "Worktree path",
+                path: worktree_dir_raw,
+                existing_branch,
             }
+            .into());
         }
-        eprintln!(
-            "Warning: worktree path already exists. Opening: {}",
-            worktree_dir_raw.display()
-        );
-        return reopen_existing_worktree(
+        Some((worktree_dir_raw.clone(), "worktree path already exists"))
+    } else if let Some(entry) = git::find_worktree_for_basename(&worktrees, &agent_name) {
+        if let Some(existing_branch) = other_branch(entry) {
+            return Err(PcError::WorktreeExists {
+                what: "A worktree directory with the same name",
+                path: entry.path.clone(),
+                existing_branch,
+            }
+            .into());
+        }
+        Some((entry.path.clone(), "worktree directory name already exists"))
+    } else {
+        None
+    };
+
+    debug!(
+        "Worktree collision checks: {:.1?}",
+        checks_started.elapsed()
+    );
+
+    if args.attach {
+        let Some(entry) = git::find_worktree_for_branch(&worktrees, &branch_name) else {
+            bail_git_state!("No worktree to attach for branch {branch_name}");
+        };
+        // `pc rm` on the main checkout would try to delete the repository itself.
+        let canonical = |p: &Path| std::fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+        if worktrees
+            .first()
+            .is_some_and(|main| canonical(&main.path) == canonical(&entry.path))
+        {
+            bail_usage!(
+                "Cannot attach the main worktree ({}); check out {branch_name} in a separate worktree first",
+                entry.path.display()
+            );
+        }
+        let recorded = AgentMeta {
+            labels: args.labels.into_iter().collect(),
+            profile: args.profile,
+            ..AgentMeta::default()
+        };
+        return attach_existing_worktree(
+            &repo_root,
             &branch_name,
             &agent_name,
-            &worktree_dir_raw,
+            &entry.path,
+            recorded,
             args.no_open,
         );
     }
 
-    if let Some(existing) = git::worktree_path_for_basename(&agent_name)? {
-        if let Some(entry) = git::worktree_entry_for_path(&existing)? {
-            if let Some(existing_ref) = entry.branch.as_deref() {
-                let wanted_ref = format!("refs/heads/{branch_name}");
-                if existing_ref != wanted_ref {
-                    bail!(
-                        "A worktree directory with the same name already exists for a different branch: {} (existing: {})",
-                        existing.display(),
-                        existing_ref.strip_prefix("refs/heads/").unwrap_or(existing_ref)
-                    );
-                }
-            }
+    if let Some((existing, what)) = existing {
+        if args.idempotent {
+            ensure_worktree_on_branch(&repo_root, &existing, &branch_name)?;
+            return reopen_existing_worktree(&branch_name, &agent_name, &existing, args.no_open);
         }
-        eprintln!(
-            "Warning: worktree directory name already exists. Opening: {}",
-            existing.display()
-        );
-        return reopen_existing_worktree(&branch_name, &agent_name, &existing, args.no_open);
+        if !args.recreate {
+            eprintln!("Warning: {what}. Opening: {}", existing.display());
+            return reopen_existing_worktree(&branch_name, &agent_name, &existing, args.no_open);
+        }
+        eprintln!("Warning: {what}. Recreating: {}", existing.display());
+        remove_for_recreate(
+            &repo_root,
+            &agent_name,
+            &existing,
+            args.force,
+            &repo_config.rm_ignore,
+        )?;
     }
 
-    git::ensure_ref_exists(&base_ref)?;
+    if !args.no_preflight {
+        preflight::check_new_agent(&repo_config, &agent_name, &worktree_base_dir)?;
+    }
 
-    let branch_exists = git::branch_exists_local(&branch_name)?;
+    let checks_started = Instant::now();
+    git::ensure_ref_exists(&repo_root, &base_ref)?;
+    let base_kind = git::base_kind(&repo_root, &base_ref)?;
+
+    let local_ref = format!("refs/heads/{branch_name}");
+    let remote_ref = format!("refs/remotes/{base_ref}");
+    let refs = git::existing_refs(&repo_root, &[local_ref.clone(), remote_ref.clone()])?;
+    let branch_exists = refs.contains(&local_ref);
+    debug!(
+        "Base and branch ref checks: {:.1?}",
+        checks_started.elapsed()
+    );
+    let (wip, dirty) = match wip {
+        Some(decided) => decided,
+        None => choose_wip_handling(
+            &repo_root,
+            &base_ref,
+            branch_exists,
+            args.include_wip,
+            args.copy_wip,
+        )?,
+    };
+    let base_ref = match wip {
+        WipHandling::Commit => commit_wip(&repo_root, &base_ref)?,
+        _ => base_ref,
+    };
+    // Tags and commit ids are not branch tips: say exactly where the branch starts.
+    let base_label = if base_kind.is_branch() || base_ref == "HEAD" || wip == WipHandling::Commit {
+        base_ref.clone()
+    } else {
+        let commit = git::rev_parse_commit(&repo_root, &base_ref)?;
+        let kind = if base_kind == git::BaseKind::Tag {
+            "tag"
+        } else {
+            "commit"
+        };
+        if branch_exists {
+            eprintln!(
+                "Warning: branch {branch_name} already exists; ignoring base {kind} {base_ref}"
+            );
+        } else {
+            info!("Base: {base_ref} ({kind}) -> {commit}");
+        }
+        format!("{base_ref} ({kind} {commit})")
+    };
     if !branch_exists {
         if exec::can_prompt() {
             eprintln!("Warning: branch does not exist: {branch_name}");
             let ok = Confirm::with_theme(&ColorfulTheme::default())
-                .with_prompt(format!("Create new branch {branch_name} from {base_ref}?"))
+                .with_prompt(trf(Msg::ConfirmCreateBranch, &[&branch_name, &base_label]))
                 .default(true)
                 .interact()
                 .context("Prompt failed")?;
             if !ok {
-                println!("Cancelled. Branch not created: {branch_name}");
+                info!("{}", trf(Msg::CancelledBranchNotCreated, &[&branch_name]));
                 return Ok(());
             }
         } else {
             eprintln!(
-                "Warning: branch does not exist: {branch_name}. Creating it from {base_ref}."
+                "Warning: branch does not exist: {branch_name}. Creating it from {base_label}."
             );
         }
     }
 
-    let created_branch = git::worktree_add(&worktree_dir_raw, &branch_name, &base_ref)?;
-
-    let worktree_dir = match std::fs::canonicalize(&worktree_dir_raw) {
-        Ok(p) => p,
-        Err(e) => {
+    let open_vscode = !args.no_open && vscode::is_available();
+    let total_steps = 2
+        + usize::from(wip == WipHandling::Copy)
+        + usize::from(template_dir.is_some())
+        + usize::from(args.env_from_base)
+        + usize::from(!companion_specs.is_empty())
+        + usize::from(args.lock.is_some())
+        + usize::from(args.push_upstream.is_some())
+        + usize::from(open_vscode);
+    let mut report = ConsoleReporter::stdout(total_steps);
+
+    report.step(&format!("Creating worktree for {branch_name}"));
+    let track = !args.no_branch_track
+        && !branch_exists
+        && wip != WipHandling::Commit
+        && refs.contains(&remote_ref);
+    let created_branch = !branch_exists;
+    // From here until the metadata is written, any error (or Ctrl-C) undoes what was
+    // created so far instead of stranding a half-made agent.
+    let rollback = interrupt::guard()?;
+    {
+        let (repo_root, agent_name, worktree_dir, branch_name) = (
+            repo_root.clone(),
+            agent_name.clone(),
+            worktree_dir_raw.clone(),
+            branch_name.clone(),
+        );
+        rollback.push(move || {
             rollback_failed_agent_new(
                 &repo_root,
                 &agent_name,
-                &worktree_dir_raw,
+                &worktree_dir,
                 &branch_name,
                 created_branch,
-            )?;
-            return Err(anyhow::Error::new(e).context(format!(
-                "Failed to resolve worktree dir: {}",
-                worktree_dir_raw.display()
-            )));
-        }
-    };
+            )
+        });
+    }
+    git::worktree_add(
+        &repo_root,
+        &worktree_dir_raw,
+        &branch_name,
+        &base_ref,
+        branch_exists,
+        track,
+        &args.sparse,
+    )?;
+
+    let worktree_dir = std::fs::canonicalize(&worktree_dir_raw).with_context(|| {
+        format!(
+            "Failed to resolve worktree dir: {}",
+            worktree_dir_raw.display()
+        )
+    })?;
 
     if agent_name != branch_name {
-        println!("Agent:    {agent_name}");
+        info!("{}", trf(Msg::SummaryAgent, &[&agent_name]));
+    }
+    info!("{}", trf(Msg::SummaryWorktree, &[&worktree_dir.display()]));
+    info!("{}", trf(Msg::SummaryBranch, &[&branch_name]));
+
+    if wip == WipHandling::Copy {
+        report.step("Copying uncommitted files");
+        copy_wip_files(&repo_root, &worktree_dir, &dirty)?;
+        info!(
+            "Copied {} uncommitted file(s) from {}",
+            dirty.len(),
+            repo_root.display()
+        );
+    }
+
+    let mut generated_files = Vec::new();
+    if let Some(dir) = template_dir.as_deref() {
+        report.step("Copying devcontainer template");
+        let written = devcontainer::copy_template_dir(dir, &worktree_dir)?;
+        info!(
+            "{}",
+            trf(Msg::SummaryDevcontainer, &[&written.len(), &dir.display()])
+        );
+        generated_files.extend(written);
+    }
+
+    if args.env_from_base {
+        report.step("Writing .env from .env.example");
+        generated_files.extend(write_env_from_base(&worktree_dir, &branch_name)?);
+    }
+
+    let mut companions = Vec::new();
+    if !companion_specs.is_empty() {
+        report.step("Adding companion worktrees");
+        companions = add_companions(&worktree_dir, &companion_specs, &rollback)?;
     }
-    println!("Worktree: {}", worktree_dir.display());
-    println!("Branch:   {branch_name}");
 
-    if let Err(e) = meta::write_agent_meta(
+    report.step("Recording agent metadata");
+    // Still inside the guard: a failure here rolls the agent back like any other.
+    let upstream = git::upstream_of(&repo_root, &branch_name)?;
+    let generated_files = record_generated_files(&worktree_dir, generated_files)?;
+    meta::write_agent_meta(
         &agent_name,
         AgentMeta {
             branch_name: Some(branch_name.clone()),
+            worktree_path: Some(worktree_dir.clone()),
+            created_at: Some(meta::now_unix_secs()),
+            generated_files,
+            labels: args.labels.into_iter().collect(),
+            upstream,
+            profile: args.profile,
+            sparse_paths: args.sparse,
+            companions,
         },
-    ) {
-        rollback_failed_agent_new(
-            &repo_root,
-            &agent_name,
-            &worktree_dir,
-            &branch_name,
-            created_branch,
-        )?;
-        return Err(e);
+    )?;
+    rollback.commit();
+
+    if let Some((name, profile)) = save_as {
+        save_profile(&repo_root, &name, &profile);
+    }
+
+    if let Some(reason) = args.lock.as_deref() {
+        report.step("Locking worktree");
+        // Like pushing, a failed lock leaves a perfectly usable agent behind.
+        if let Err(e) = git::worktree_lock(&repo_root, &worktree_dir, reason) {
+            eprintln!("Warning: failed to lock {}: {e:#}", worktree_dir.display());
+        }
+    }
+
+    if let Some(remote) = args.push_upstream.as_deref() {
+        report.step(&format!("Pushing {branch_name} to {remote}"));
+        // The agent is usable without the remote branch; report and keep going.
+        match push_upstream(&repo_root, &agent_name, &worktree_dir, remote, &branch_name) {
+            Ok(upstream) => info!("Upstream: {upstream}"),
+            Err(e) => eprintln!("Warning: failed to push {branch_name} to {remote}: {e:#}"),
+        }
     }
 
-    if !args.no_open && exec::is_in_path("code") {
+    if open_vscode {
+        report.step("Opening VS Code");
         if let Err(e) = vscode::open_vscode_local(&worktree_dir) {
             eprintln!("Warning: failed to open VS Code: {e:#}");
         }
     }
 
+    report.finish(&next_step_hints(
+        &agent_name,
+        &branch_name,
+        &worktree_dir,
+        open_vscode,
+    ));
     Ok(())
 }
 
-fn resolve_base_ref(args: &AgentNewArgs) -> Result<Option<String>> {
+/// Follow-up commands shown after `pc new` finishes.
+fn next_step_hints(agent: &str, branch: &str, worktree: &Path, opened: bool) -> Vec<String> {
+    let mut hints = vec![format!("cd \"$(pc path {agent})\"")];
+    if !opened {
+        hints.push(format!("code {}", worktree.display()));
+    }
+    hints.push(format!("pc rm {branch}"));
+    hints
+}
+
+fn resolve_base_ref(repo_root: &Path, args: &AgentNewArgs) -> Result<Option<String>> {
     if args.select_base && args.base.is_some() {
-        bail!("Use either --base or --select-base, not both.");
+        bail_usage!("Use either --base or --select-base, not both.");
     }
 
     if args.select_base {
-        return select_base_branch_tui();
+        return select_base_branch_tui(repo_root);
     }
 
     match args.base.clone() {
-        Some(v) if v == "__tui__" => select_base_branch_tui(),
+        Some(v) if v == "__tui__" => select_base_branch_tui(repo_root),
         Some(v) => Ok(Some(v)),
         None => Ok(Some("HEAD".to_string())),
     }
@@ -215,14 +489,16 @@ fn resolve_base_ref(args: &AgentNewArgs) -> Result<Option<String>> {
 
 fn prompt_new_branch_name(base_ref: &str) -> Result<String> {
     if !dialoguer::console::Term::stdout().is_term() {
-        bail!("No branch specified and no TTY available. Pass a branch name: `pc new <branch>`.");
+        bail_usage!(
+            "No branch specified and no TTY available. Pass a branch name: `pc new <branch>`."
+        );
     }
 
     let branch = Input::<String>::with_theme(&ColorfulTheme::default())
-        .with_prompt(format!("New branch name (base: {base_ref})"))
+        .with_prompt(trf(Msg::PromptNewBranchName, &[&base_ref]))
         .validate_with(|s: &String| {
             if s.trim().is_empty() {
-                return Err("Branch name cannot be empty".to_string());
+                return Err(tr(Msg::BranchNameEmpty).to_string());
             }
             Ok(())
         })
@@ -241,12 +517,12 @@ fn reopen_existing_worktree(
     let worktree_dir =
         std::fs::canonicalize(worktree_dir).unwrap_or_else(|_| worktree_dir.to_path_buf());
     if agent_name != branch_name {
-        println!("Agent:    {agent_name}");
+        info!("{}", trf(Msg::SummaryAgent, &[&agent_name]));
     }
-    println!("Worktree: {}", worktree_dir.display());
-    println!("Branch:   {branch_name}");
+    info!("{}", trf(Msg::SummaryWorktree, &[&worktree_dir.display()]));
+    info!("{}", trf(Msg::SummaryBranch, &[&branch_name]));
 
-    if !no_open && exec::is_in_path("code") {
+    if !no_open && vscode::is_available() {
         if let Err(e) = vscode::open_vscode_local(&worktree_dir) {
             eprintln!("Warning: failed to open VS Code: {e:#}");
         }
@@ -254,6 +530,78 @@ fn reopen_existing_worktree(
     Ok(())
 }
 
+/// `pc new --attach`: record an existing worktree of the branch as this agent (unless it
+/// already is), then open it like a re-run of `pc new` would. `recorded` carries the
+/// labels/profile to store.
+fn attach_existing_worktree(
+    repo_root: &Path,
+    branch_name: &str,
+    agent_name: &str,
+    worktree_dir: &Path,
+    recorded: AgentMeta,
+    no_open: bool,
+) -> Result<()> {
+    let worktree_dir = std::fs::canonicalize(worktree_dir)
+        .with_context(|| format!("Failed to resolve {}", worktree_dir.display()))?;
+    match meta::read_agent_meta(agent_name)? {
+        Some(m) => {
+            let recorded_path = m
+                .worktree_path
+                .as_deref()
+                .map(|p| std::fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf()));
+            if recorded_path.as_deref() != Some(worktree_dir.as_path()) {
+                bail_git_state!(
+                    "Agent {agent_name} is already recorded for another worktree; pass --agent-name to attach under a different name"
+                );
+            }
+        }
+        None => {
+            meta::write_agent_meta(
+                agent_name,
+                AgentMeta {
+                    branch_name: Some(branch_name.to_string()),
+                    worktree_path: Some(worktree_dir.clone()),
+                    created_at: Some(meta::now_unix_secs()),
+                    upstream: git::upstream_of(repo_root, branch_name)?,
+                    ..recorded
+                },
+            )?;
+            info!("Attached existing worktree as agent {agent_name}");
+        }
+    }
+    reopen_existing_worktree(branch_name, agent_name, &worktree_dir, no_open)
+}
+
+/// `pc path <agent>`: print the agent's worktree path and nothing else, found the
+/// same way as `pc rm` (metadata, base dir, branch).
+pub(crate) fn cmd_path(args: PathArgs) -> Result<()> {
+    exec::ensure_in_path("git")?;
+
+    let repo_root = git::repo_root()?;
+    let repo_name = repo_root
+        .file_name()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| anyhow!("Failed to get repo name from path: {}", repo_root.display()))?
+        .to_string();
+
+    let repo_config = config::load_repo_config(&repo_root)?;
+    let worktree_base_dir =
+        resolve_worktree_base_dir(&repo_root, &repo_name, args.base_dir, repo_config.base_dir)?;
+
+    let resolved = resolve_agent_arg(&repo_root, &args.agent)?;
+    let (worktree_dir, _) = locate_agent_worktree(
+        &repo_root,
+        &resolved,
+        &worktree_base_dir,
+        repo_config.worktree_path_template.as_deref(),
+        &repo_name,
+    )?;
+    let worktree_dir = std::fs::canonicalize(&worktree_dir)
+        .with_context(|| format!("Failed to resolve {}", worktree_dir.display()))?;
+    println!("{}", worktree_dir.display());
+    Ok(())
+}
+
 pub(crate) fn cmd_rm(args: AgentRmArgs) -> Result<()> {
     exec::ensure_in_path("git")?;
 
@@ -262,6 +610,7 @@ pub(crate) fn cmd_rm(args: AgentRmArgs) -> Result<()> {
         agent_name: arg_agent_name,
         base_dir,
         force,
+        ignore,
     } = args;
 
     let repo_root = git::repo_root()?;
@@ -271,45 +620,73 @@ pub(crate) fn cmd_rm(args: AgentRmArgs) -> Result<()> {
         .ok_or_else(|| anyhow!("Failed to get repo name from path: {}", repo_root.display()))?
         .to_string();
 
-    let worktree_base_dir = resolve_worktree_base_dir(&repo_root, &repo_name, base_dir)?;
+    let repo_config = config::load_repo_config(&repo_root)?;
+    let worktree_base_dir =
+        resolve_worktree_base_dir(&repo_root, &repo_name, base_dir, repo_config.base_dir)?;
 
     if arg_branch_name.is_none() && arg_agent_name.is_some() {
-        bail!("--agent-name requires an explicit branch name (or select a worktree and omit --agent-name).");
+        bail_usage!("--agent-name requires an explicit branch name (or select a worktree and omit --agent-name).");
     }
 
-    let (branch_name, agent_name, worktree_dir_raw, should_remove_meta) = match arg_branch_name {
-        Some(branch_name) => {
-            git::ensure_branch_name_valid(&branch_name)?;
-
-            let agent_name = match arg_agent_name {
-                Some(v) => {
-                    if !is_valid_agent_name(&v) {
-                        bail!("agent-name must match: [A-Za-z0-9._-]+ (and cannot be '.' or '..')");
-                    }
-                    v
+    let resolved = match arg_branch_name {
+        Some(arg) => Some(match arg_agent_name {
+            Some(v) => {
+                git::ensure_branch_name_valid(&repo_root, &arg)?;
+                if !is_valid_agent_name(&v) {
+                    bail_usage!(
+                        "agent-name must match: [A-Za-z0-9._-]+ (and cannot be '.' or '..')"
+                    );
                 }
-                None => derive_agent_name_from_branch(&branch_name)?,
-            };
-
-            let expected_dir = worktree_base_dir.join(&agent_name);
-            let worktree_dir = if expected_dir.exists() {
-                expected_dir
-            } else if let Some(p) = git::worktree_path_for_branch(&branch_name)? {
-                p
+                let meta = meta::read_agent_meta(&v)?;
+                ResolvedAgent {
+                    agent_name: v,
+                    branch_name: arg,
+                    meta,
+                }
+            }
+            None if is_worktree_path_arg(&repo_root, &arg)? => {
+                resolve_agent_by_path(&repo_root, Path::new(&arg))?
+            }
+            None => resolve_agent_arg(&repo_root, &arg)?,
+        }),
+        None => {
+            if !dialoguer::console::Term::stdout().is_term() {
+                bail_usage!("No worktree specified and no TTY available. Pass a branch name: `pc rm <branch>`.");
+            }
+            let agents = recorded_agents_by_recent()?;
+            if agents.is_empty() {
+                None
             } else {
-                bail!(
-                    "Agent worktree not found. Expected path: {} (branch: {})",
-                    expected_dir.display(),
-                    branch_name
-                );
-            };
+                let Some(selected) = select_agent_tui(tr(Msg::SelectAgentToRemove), agents)? else {
+                    info!("{}", tr(Msg::Cancelled));
+                    return Ok(());
+                };
+                Some(selected)
+            }
+        }
+    };
 
-            (Some(branch_name), agent_name, worktree_dir, true)
+    let (branch_name, agent_name, worktree_dir_raw, should_remove_meta) = match resolved {
+        Some(resolved) => {
+            let (worktree_dir, source) = locate_agent_worktree(
+                &repo_root,
+                &resolved,
+                &worktree_base_dir,
+                repo_config.worktree_path_template.as_deref(),
+                &repo_name,
+            )?;
+            info!("Found worktree via {source}: {}", worktree_dir.display());
+            (
+                Some(resolved.branch_name),
+                resolved.agent_name,
+                worktree_dir,
+                true,
+            )
         }
         None => {
             let selected = select_worktree_to_remove_tui(&repo_root, &worktree_base_dir)?;
             let Some(selected) = selected else {
-                println!("Cancelled.");
+                info!("{}", tr(Msg::Cancelled));
                 return Ok(());
             };
             (
@@ -327,31 +704,67 @@ pub(crate) fn cmd_rm(args: AgentRmArgs) -> Result<()> {
     if exec::can_prompt() {
         let ok = confirm_double_rm(&worktree_dir, branch_name.as_deref(), &agent_name)?;
         if !ok {
-            println!(
-                "Cancelled. Worktree not removed: {}",
-                worktree_dir.display()
+            info!(
+                "{}",
+                trf(Msg::CancelledWorktreeNotRemoved, &[&worktree_dir.display()])
             );
             return Ok(());
         }
     }
 
-    // Best-effort: ignore typical generated dirs so `git worktree remove` doesn't
-    // require `--force` after normal local development (e.g. uv creates .venv).
-    git::ensure_exclude(&worktree_dir, ".venv/")?;
-    git::ensure_exclude(&worktree_dir, "node_modules/")?;
-    git::ensure_exclude(&worktree_dir, "target/")?;
-    git::ensure_exclude(&worktree_dir, ".pytest_cache/")?;
-    git::ensure_exclude(&worktree_dir, ".ruff_cache/")?;
-
-    let removed = git::worktree_remove(&worktree_dir, force)?;
+    let ignore: Vec<String> = repo_config
+        .rm_ignore
+        .iter()
+        .chain(&ignore)
+        .cloned()
+        .collect();
+    let recorded = if should_remove_meta {
+        meta::read_agent_meta(&agent_name)?
+    } else {
+        None
+    };
+    let generated = unchanged_generated_files(recorded.as_ref(), &worktree_dir)?;
+    let companions = recorded
+        .as_ref()
+        .map(|m| m.companions.clone())
+        .unwrap_or_default();
+    // Settle --force (for the companions too) before deleting anything, so a refused rm
+    // leaves everything in place.
+    let Some(force) =
+        confirm_force_if_dirty(&worktree_dir, &generated, &ignore, &companions, force)?
+    else {
+        info!(
+            "{}",
+            trf(Msg::CancelledWorktreeNotRemoved, &[&worktree_dir.display()])
+        );
+        return Ok(());
+    };
+    leave_worktree(&repo_root, &worktree_dir)?;
+    let lock_reason = unlock_if_locked(&repo_root, &worktree_dir)?;
+    let keep_agent = || {
+        relock(&repo_root, &worktree_dir, lock_reason.as_deref());
+        forget_companions(&agent_name, recorded.as_ref());
+    };
+    // Companion worktrees live inside this one, so they have to go first.
+    remove_recorded_companions(&companions, force).inspect_err(|_| keep_agent())?;
+
+    let removed = git::worktree_remove(
+        &repo_root,
+        &worktree_dir,
+        removal_force(force, &generated, &ignore),
+    )
+    .inspect_err(|_| keep_agent())?;
     if !removed {
-        println!(
-            "Cancelled. Worktree not removed: {}",
-            worktree_dir.display()
+        keep_agent();
+        info!(
+            "{}",
+            trf(Msg::CancelledWorktreeNotRemoved, &[&worktree_dir.display()])
         );
         return Ok(());
     }
 
+    remove_empty_parents(&worktree_dir, &worktree_base_dir);
+
     if should_remove_meta {
         meta::remove_agent_meta(&agent_name)?;
     } else {
@@ -361,13 +774,767 @@ pub(crate) fn cmd_rm(args: AgentRmArgs) -> Result<()> {
     }
 
     if let Some(branch_name) = branch_name.as_deref() {
-        println!("Removed worktree for {branch_name}");
+        info!("{}", trf(Msg::RemovedWorktreeForBranch, &[&branch_name]));
     } else {
-        println!("Removed worktree {}", worktree_dir.display());
+        info!("{}", trf(Msg::RemovedWorktree, &[&worktree_dir.display()]));
     }
     Ok(())
 }
 
+#[derive(Debug)]
+struct ResolvedAgent {
+    agent_name: String,
+    branch_name: String,
+    meta: Option<AgentMeta>,
+}
+
+/// Resolve a user-supplied agent argument that may be either an agent name or a branch name.
+///
+/// Order: metadata recorded under that agent name, then metadata whose `branch_name` equals
+/// the argument, then deriving the agent name from the argument as a branch name.
+fn resolve_agent_arg(repo_root: &Path, arg: &str) -> Result<ResolvedAgent> {
+    let by_name = if is_valid_agent_name(arg) {
+        meta::read_agent_meta(arg)?
+    } else {
+        None
+    };
+    let mut by_branch: Vec<(String, AgentMeta)> = meta::list_agent_metas()?
+        .into_iter()
+        .filter(|(name, m)| name != arg && m.branch_name.as_deref() == Some(arg))
+        .collect();
+
+    if let Some(m) = by_name {
+        if let Some((other, _)) = by_branch.first() {
+            bail_usage!("Ambiguous agent {arg:?}: it is an agent name and also the branch of agent {other:?}. Use --agent-name to disambiguate."
+            );
+        }
+        let branch_name = m.branch_name.clone().unwrap_or_else(|| arg.to_string());
+        return Ok(ResolvedAgent {
+            agent_name: arg.to_string(),
+            branch_name,
+            meta: Some(m),
+        });
+    }
+
+    if by_branch.len() > 1 {
+        let names: Vec<&str> = by_branch.iter().map(|(n, _)| n.as_str()).collect();
+        bail_usage!(
+            "Ambiguous branch {arg:?}: recorded for agents {}. Use --agent-name to disambiguate.",
+            names.join(", ")
+        );
+    }
+    if let Some((agent_name, m)) = by_branch.pop() {
+        return Ok(ResolvedAgent {
+            agent_name,
+            branch_name: arg.to_string(),
+            meta: Some(m),
+        });
+    }
+
+    git::ensure_branch_name_valid(repo_root, arg)?;
+    let agent_name = derive_agent_name_from_branch(arg).or_usage()?;
+    let meta = meta::read_agent_meta(&agent_name)?;
+    Ok(ResolvedAgent {
+        agent_name,
+        branch_name: arg.to_string(),
+        meta,
+    })
+}
+
+/// `pc rm .` from inside the worktree: move to the main worktree first, so the git
+/// commands that follow the removal still have a working directory.
+fn leave_worktree(repo_root: &Path, worktree_dir: &Path) -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let cwd = std::fs::canonicalize(&cwd).unwrap_or(cwd);
+    if !cwd.starts_with(worktree_dir) {
+        return Ok(());
+    }
+    let main = git::worktrees(repo_root)?
+        .into_iter()
+        .next()
+        .context("git worktree list returned no worktrees")?;
+    std::env::set_current_dir(&main.path)
+        .with_context(|| format!("Failed to change directory to {}", main.path.display()))
+}
+
+/// `pc rm <arg>` treats `arg` as a path when it is spelled like one (`.`, `..`, `/…`,
+/// `./…`, `../…`) or names a directory inside a linked worktree; otherwise it is an
+/// agent or branch name.
+fn is_worktree_path_arg(repo_root: &Path, arg: &str) -> Result<bool> {
+    let spelled_as_path = arg == "."
+        || arg == ".."
+        || arg.starts_with('/')
+        || arg.starts_with("./")
+        || arg.starts_with("../");
+    if spelled_as_path {
+        return Ok(true);
+    }
+    let path = Path::new(arg);
+    Ok(path.is_dir() && linked_worktree_containing(&git::worktrees(repo_root)?, path).is_some())
+}
+
+/// The linked (non-main) worktree that contains `path`, innermost first.
+fn linked_worktree_containing<'a>(
+    worktrees: &'a [git::WorktreeEntry],
+    path: &Path,
+) -> Option<&'a git::WorktreeEntry> {
+    let path = std::fs::canonicalize(path).ok()?;
+    worktrees
+        .iter()
+        .skip(1) // `git worktree list` puts the main worktree first
+        .filter_map(|e| std::fs::canonicalize(&e.path).ok().map(|p| (e, p)))
+        .filter(|(_, p)| path.starts_with(p))
+        .max_by_key(|(_, p)| p.components().count())
+        .map(|(e, _)| e)
+}
+
+/// Identify the agent owning the worktree at (or containing) `path`: the metadata that
+/// recorded that worktree, else the worktree's directory name and checked-out branch.
+fn resolve_agent_by_path(repo_root: &Path, path: &Path) -> Result<ResolvedAgent> {
+    if !path.is_dir() {
+        bail_usage!("Not a directory: {}", path.display());
+    }
+    let worktrees = git::worktrees(repo_root)?;
+    let Some(entry) = linked_worktree_containing(&worktrees, path) else {
+        bail_git_state!(
+            "Not an agent worktree of this repository: {} (the main worktree cannot be removed)",
+            path.display()
+        );
+    };
+    let worktree_dir = std::fs::canonicalize(&entry.path)
+        .with_context(|| format!("Failed to resolve {}", entry.path.display()))?;
+    let checked_out = entry
+        .branch
+        .as_deref()
+        .and_then(|b| b.strip_prefix("refs/heads/"))
+        .map(str::to_string);
+
+    let recorded = meta::list_agent_metas()?.into_iter().find(|(_, m)| {
+        m.worktree_path
+            .as_deref()
+            .and_then(|p| std::fs::canonicalize(p).ok())
+            .is_some_and(|p| p == worktree_dir)
+    });
+    if let Some((agent_name, m)) = recorded {
+        let branch_name = m
+            .branch_name
+            .clone()
+            .or(checked_out)
+            .unwrap_or_else(|| agent_name.clone());
+        return Ok(ResolvedAgent {
+            agent_name,
+            branch_name,
+            meta: Some(m),
+        });
+    }
+
+    let agent_name = worktree_dir
+        .file_name()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| {
+            anyhow!(
+                "Failed to get agent name from path: {}",
+                worktree_dir.display()
+            )
+        })?
+        .to_string();
+    let meta = if is_valid_agent_name(&agent_name) {
+        meta::read_agent_meta(&agent_name)?
+    } else {
+        None
+    };
+    Ok(ResolvedAgent {
+        branch_name: checked_out.unwrap_or_else(|| agent_name.clone()),
+        agent_name,
+        meta,
+    })
+}
+
+/// Locate an agent's worktree: recorded metadata path (if it is still a registered
+/// worktree), then the path `pc new` would use now, then the worktree checked out on its
+/// branch. Returns the path and which of those sources found it.
+fn locate_agent_worktree(
+    repo_root: &Path,
+    resolved: &ResolvedAgent,
+    worktree_base_dir: &Path,
+    path_template: Option<&str>,
+    repo_name: &str,
+) -> Result<(PathBuf, &'static str)> {
+    let worktrees = git::worktrees(repo_root)?;
+    let recorded = resolved.meta.as_ref().and_then(|m| m.worktree_path.clone());
+    let recorded = match recorded {
+        Some(p) if p.exists() && git::find_worktree_for_path(&worktrees, &p).is_some() => Some(p),
+        _ => None,
+    };
+
+    // A template with {date} or {user} may no longer produce the creation-time path;
+    // the recorded path and the branch lookup cover that.
+    let expected_dir = agent_worktree_dir(
+        worktree_base_dir,
+        path_template,
+        repo_name,
+        &resolved.agent_name,
+        &resolved.branch_name,
+    )
+    .unwrap_or_else(|_| worktree_base_dir.join(&resolved.agent_name));
+    let (worktree_dir, source) = if let Some(p) = recorded {
+        (p, "agent metadata")
+    } else if expected_dir.exists() {
+        (expected_dir, "base dir")
+    } else if let Some(e) = git::find_worktree_for_branch(&worktrees, &resolved.branch_name) {
+        (e.path.clone(), "branch")
+    } else if let Some(e) = git::find_worktree_for_basename(&worktrees, &resolved.agent_name)
+        .filter(|e| e.branch.is_none())
+    {
+        // Detached (e.g. its branch was deleted): only the directory name is left to match.
+        (e.path.clone(), "worktree name")
+    } else {
+        bail_git_state!(
+            "Agent worktree not found. Expected path: {} (branch: {})",
+            expected_dir.display(),
+            resolved.branch_name
+        );
+    };
+    warn_if_branch_missing(repo_root, &worktrees, &worktree_dir, &resolved.branch_name)?;
+    Ok((worktree_dir, source))
+}
+
+/// The branch of an agent can be deleted with plain git while its worktree lives on
+/// (detached, or still pointing at the missing ref). Say so instead of assuming it exists.
+fn warn_if_branch_missing(
+    repo_root: &Path,
+    worktrees: &[git::WorktreeEntry],
+    worktree_dir: &Path,
+    branch_name: &str,
+) -> Result<()> {
+    if git::branch_exists_local(repo_root, branch_name)? {
+        return Ok(());
+    }
+    let state = match git::find_worktree_for_path(worktrees, worktree_dir) {
+        Some(e) if e.branch.is_none() => "detached HEAD",
+        _ => "a branch that no longer exists",
+    };
+    eprintln!(
+        "Warning: branch {branch_name} no longer exists; worktree {} is on {state}",
+        worktree_dir.display()
+    );
+    Ok(())
+}
+
+/// Recorded agents with a known branch, most recently created first.
+fn recorded_agents_by_recent() -> Result<Vec<(String, AgentMeta)>> {
+    let mut agents: Vec<(String, AgentMeta)> = meta::list_agent_metas()?
+        .into_iter()
+        .filter(|(_, m)| m.branch_name.is_some())
+        .collect();
+    sort_agents_by_recent(&mut agents);
+    Ok(agents)
+}
+
+fn sort_agents_by_recent(agents: &mut [(String, AgentMeta)]) {
+    // Newest first; agents recorded before `created_at` existed go last, by name.
+    agents.sort_by(|a, b| {
+        b.1.created_at
+            .cmp(&a.1.created_at)
+            .then_with(|| a.0.cmp(&b.0))
+    });
+}
+
+fn select_agent_tui(
+    prompt: &str,
+    agents: Vec<(String, AgentMeta)>,
+) -> Result<Option<ResolvedAgent>> {
+    let items: Vec<String> = agents
+        .iter()
+        .map(|(name, m)| {
+            let branch = m.branch_name.as_deref().unwrap_or("?");
+            match m.worktree_path.as_deref() {
+                Some(p) => format!("{name}  ({branch})  —  {}", p.display()),
+                None => format!("{name}  ({branch})"),
+            }
+        })
+        .collect();
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(&items)
+        .default(0)
+        .interact_opt()
+        .context("TUI selection failed")?;
+
+    let Some(idx) = selection else {
+        return Ok(None);
+    };
+    let (agent_name, meta) = agents
+        .into_iter()
+        .nth(idx)
+        .expect("selection index in range");
+    let branch_name = meta
+        .branch_name
+        .clone()
+        .unwrap_or_else(|| agent_name.clone());
+    Ok(Some(ResolvedAgent {
+        agent_name,
+        branch_name,
+        meta: Some(meta),
+    }))
+}
+
+/// `pc new <branch> --count N`: create `<branch>-1` .. `<branch>-N` from `base_ref`.
+/// All names are checked up front; each agent is created (and rolled back on failure)
+/// by the regular `pc new` path.
+fn cmd_new_numbered(
+    repo_root: &Path,
+    args: AgentNewArgs,
+    base_ref: String,
+    count: u32,
+) -> Result<()> {
+    let Some(branch_prefix) = args.branch_name.clone() else {
+        bail_usage!("--count requires a branch name");
+    };
+    let agent_prefix = match args.agent_name.clone() {
+        Some(v) => v,
+        None => derive_agent_name_from_branch(&branch_prefix).or_usage()?,
+    };
+
+    let mut planned = Vec::new();
+    for i in 1..=count {
+        let branch_name = format!("{branch_prefix}-{i}");
+        let agent_name = format!("{agent_prefix}-{i}");
+        git::ensure_branch_name_valid(repo_root, &branch_name)?;
+        if !is_valid_agent_name(&agent_name) || agent_name.len() > MAX_AGENT_NAME_LEN {
+            bail_usage!(
+                "Agent name {agent_name:?} is invalid or longer than {MAX_AGENT_NAME_LEN} characters; use a shorter --agent-name"
+            );
+        }
+        if git::branch_exists_local(repo_root, &branch_name)? {
+            bail_git_state!("Branch already exists: {branch_name}");
+        }
+        if meta::read_agent_meta(&agent_name)?.is_some() {
+            bail_git_state!("Agent already exists: {agent_name}");
+        }
+        planned.push((branch_name, agent_name));
+    }
+
+    // Decide about uncommitted changes once: every agent gets the same WIP commit or copy.
+    let (wip, dirty) =
+        choose_wip_handling(repo_root, &base_ref, false, args.include_wip, args.copy_wip)?;
+    let (wip, base_ref) = match wip {
+        WipHandling::Commit => (WipHandling::Continue, commit_wip(repo_root, &base_ref)?),
+        other => (other, base_ref),
+    };
+
+    let mut created = Vec::new();
+    for (branch_name, agent_name) in planned {
+        let mut one = args.clone();
+        one.count = None;
+        one.branch_name = Some(branch_name);
+        one.agent_name = Some(agent_name.clone());
+        one.base = Some(base_ref.clone());
+        one.select_base = false;
+        one.fetch = false;
+        one.save_profile = None;
+        new_agent(one, Some((wip, dirty.clone()))).with_context(|| {
+            if created.is_empty() {
+                format!("Failed to create agent {agent_name}")
+            } else {
+                format!(
+                    "Failed to create agent {agent_name} (already created: {})",
+                    created.join(", ")
+                )
+            }
+        })?;
+        created.push(agent_name);
+    }
+    info!("Created {} agents: {}", created.len(), created.join(", "));
+    Ok(())
+}
+
+/// Push the agent branch to `remote`, set it as upstream and record it in the metadata.
+fn push_upstream(
+    repo_root: &Path,
+    agent_name: &str,
+    worktree_dir: &Path,
+    remote: &str,
+    branch_name: &str,
+) -> Result<String> {
+    git::push_set_upstream(worktree_dir, remote, branch_name)?;
+    let upstream = git::upstream_of(repo_root, branch_name)?
+        .unwrap_or_else(|| format!("{remote}/{branch_name}"));
+    if let Some(mut m) = meta::read_agent_meta(agent_name)? {
+        m.upstream = Some(upstream.clone());
+        meta::write_agent_meta(agent_name, m)?;
+    }
+    Ok(upstream)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WipHandling {
+    Continue,
+    Commit,
+    Copy,
+}
+
+/// Decide what to do with uncommitted changes in the worktree `pc new` runs from.
+/// Flags win; otherwise ask on a TTY, and just warn when non-interactive.
+fn choose_wip_handling(
+    repo_root: &Path,
+    base_ref: &str,
+    branch_exists: bool,
+    include_wip: bool,
+    copy_wip: bool,
+) -> Result<(WipHandling, Vec<PathBuf>)> {
+    let mut dirty = git::dirty_paths(repo_root)?;
+    // pc's own config (e.g. just edited by `--save-profile`) is not work to carry over.
+    dirty.retain(|p| p.file_name() != Some(std::ffi::OsStr::new(config::REPO_CONFIG_FILE)));
+    if dirty.is_empty() {
+        return Ok((WipHandling::Continue, dirty));
+    }
+
+    let handling = if include_wip {
+        WipHandling::Commit
+    } else if copy_wip {
+        WipHandling::Copy
+    } else if exec::can_prompt() {
+        let mut options = vec![(WipHandling::Continue, tr(Msg::WipContinue))];
+        if !branch_exists {
+            options.push((WipHandling::Commit, tr(Msg::WipCommit)));
+        }
+        options.push((WipHandling::Copy, tr(Msg::WipCopy)));
+        let items: Vec<&str> = options.iter().map(|(_, label)| *label).collect();
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt(trf(Msg::WipPrompt, &[&dirty.len(), &base_ref]))
+            .items(&items)
+            .default(0)
+            .interact()
+            .context("TUI selection failed")?;
+        options[selection].0
+    } else {
+        eprintln!(
+            "Warning: current worktree has {} uncommitted change(s); the new worktree starts from {base_ref} without them (use --include-wip or --copy-wip)",
+            dirty.len()
+        );
+        WipHandling::Continue
+    };
+
+    if handling == WipHandling::Commit && branch_exists {
+        eprintln!("Warning: branch already exists; --include-wip ignored");
+        return Ok((WipHandling::Continue, dirty));
+    }
+    Ok((handling, dirty))
+}
+
+/// Snapshot the uncommitted changes into a WIP commit on top of `base_ref`, which must
+/// be the current HEAD, and return the commit to start from.
+fn commit_wip(repo_root: &Path, base_ref: &str) -> Result<String> {
+    if git::rev_parse_commit(repo_root, base_ref)? != git::rev_parse_commit(repo_root, "HEAD")? {
+        bail_usage!(
+            "--include-wip needs the base to be the current HEAD (base: {base_ref}); use --copy-wip instead"
+        );
+    }
+    let commit = git::commit_wip_snapshot(repo_root)?;
+    info!("WIP commit: {commit}");
+    Ok(commit)
+}
+
+/// Mirror the uncommitted state of `paths` from `src` into `dst`: copy files that
+/// exist in `src`, delete the ones that were removed there.
+fn copy_wip_files(src: &Path, dst: &Path, paths: &[PathBuf]) -> Result<()> {
+    for rel in paths {
+        let from = src.join(rel);
+        let to = dst.join(rel);
+        if from.is_file() {
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            std::fs::copy(&from, &to)
+                .with_context(|| format!("Failed to copy {}", from.display()))?;
+        } else if !from.exists() && to.is_file() {
+            std::fs::remove_file(&to)
+                .with_context(|| format!("Failed to remove {}", to.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Fill options not given on the command line from a `.pc.toml` agent profile.
+/// Labels are merged per key, with `--label` winning.
+fn apply_profile(args: &mut AgentNewArgs, profile: &config::AgentProfile) {
+    if args.base.is_none() && !args.select_base {
+        args.base = profile.base.clone();
+    }
+    if args.template_dir.is_none() {
+        args.template_dir = profile.template_dir.clone();
+    }
+    let cli_labels = std::mem::take(&mut args.labels);
+    args.labels = profile
+        .labels
+        .clone()
+        .into_iter()
+        .chain(cli_labels)
+        .collect();
+    args.no_open |= profile.no_open;
+    args.no_preflight |= profile.no_preflight;
+}
+
+fn profile_from_args(args: &AgentNewArgs) -> Result<config::AgentProfile> {
+    let template_dir = match args.template_dir.as_deref() {
+        Some(dir) => {
+            devcontainer::validate_template_dir(dir)?;
+            Some(
+                std::fs::canonicalize(dir)
+                    .with_context(|| format!("Failed to resolve {}", dir.display()))?,
+            )
+        }
+        None => None,
+    };
+    Ok(config::AgentProfile {
+        base: args.base.clone().filter(|b| b != "__tui__"),
+        template_dir,
+        labels: args.labels.iter().cloned().collect(),
+        no_open: args.no_open,
+        no_preflight: args.no_preflight,
+    })
+}
+
+/// Write `profile` to the repo's `.pc.toml`, next to the profiles `--profile` reads.
+/// Profiles name repo-specific bases and templates, so there is no user-level copy.
+/// The agent already exists at this point; a failed save is only reported.
+fn save_profile(repo_root: &Path, name: &str, profile: &config::AgentProfile) {
+    let saved = config::find_repo_config(repo_root).and_then(|found| {
+        let path = found.unwrap_or_else(|| repo_root.join(config::REPO_CONFIG_FILE));
+        config::save_agent_profile(&path, name, profile).map(|()| path)
+    });
+    match saved {
+        Ok(path) => info!("Saved agent profile {name} to {}", path.display()),
+        Err(e) => eprintln!("Warning: failed to save agent profile {name}: {e:#}"),
+    }
+}
+
+fn ensure_worktree_on_branch(
+    repo_root: &Path,
+    worktree_dir: &Path,
+    branch_name: &str,
+) -> Result<()> {
+    let wanted_ref = format!("refs/heads/{branch_name}");
+    match git::worktree_entry_for_path(repo_root, worktree_dir)? {
+        Some(entry) if entry.branch.as_deref() == Some(wanted_ref.as_str()) => Ok(()),
+        Some(_) => bail_git_state!(
+            "Existing worktree is not on branch {branch_name}: {}",
+            worktree_dir.display()
+        ),
+        None => bail_git_state!(
+            "Path exists but is not a git worktree: {}",
+            worktree_dir.display()
+        ),
+    }
+}
+
+/// Record `paths` (relative, just written by pc) with their content hashes.
+fn record_generated_files(
+    worktree_dir: &Path,
+    paths: Vec<PathBuf>,
+) -> Result<Vec<meta::GeneratedFile>> {
+    let blobs = git::hash_objects(worktree_dir, &paths)?;
+    Ok(paths
+        .into_iter()
+        .zip(blobs)
+        .map(|(path, blob)| meta::GeneratedFile {
+            path,
+            blob: Some(blob),
+        })
+        .collect())
+}
+
+/// Generated files of `recorded` that are still byte-identical to what pc wrote. Only
+/// these are deleted on removal; edited ones are the user's and count as changes.
+fn unchanged_generated_files(
+    recorded: Option<&AgentMeta>,
+    worktree_dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    let Some(m) = recorded else {
+        return Ok(Vec::new());
+    };
+    if let Some(path) = m.worktree_path.as_deref() {
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if path != worktree_dir {
+            return Ok(Vec::new());
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for file in &m.generated_files {
+        if !file
+            .path
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)))
+        {
+            eprintln!(
+                "Warning: ignoring unsafe generated path in agent metadata: {}",
+                file.path.display()
+            );
+            continue;
+        }
+        if let Some(blob) = file.blob.as_deref() {
+            if worktree_dir.join(&file.path).is_file() {
+                candidates.push((file.path.clone(), blob));
+            }
+        }
+    }
+    let paths: Vec<PathBuf> = candidates.iter().map(|(p, _)| p.clone()).collect();
+    let current = git::hash_objects(worktree_dir, &paths)?;
+    Ok(candidates
+        .into_iter()
+        .zip(current)
+        .filter(|((_, blob), now)| *blob == now.as_str())
+        .map(|((path, _), _)| path)
+        .collect())
+}
+
+/// Changes that removal would discard: in `worktree_dir` (not counting `generated` and
+/// untracked files matching the `ignore` patterns) and in the companion worktrees inside
+/// it (reported relative to `worktree_dir`).
+fn uncommitted_changes(
+    worktree_dir: &Path,
+    generated: &[PathBuf],
+    ignore: &[String],
+    companions: &[meta::Companion],
+) -> Result<Vec<PathBuf>> {
+    let disposable = git::untracked_matching(worktree_dir, ignore)?;
+    let mut changes: Vec<PathBuf> = git::dirty_paths(worktree_dir)?
+        .into_iter()
+        .filter(|p| !generated.contains(p) && !disposable.contains(p))
+        .collect();
+    for c in companions {
+        if !c.worktree_path.exists() {
+            continue;
+        }
+        let prefix = c
+            .worktree_path
+            .strip_prefix(worktree_dir)
+            .unwrap_or(&c.worktree_path);
+        changes.extend(
+            git::dirty_paths(&c.worktree_path)?
+                .into_iter()
+                .map(|p| prefix.join(p)),
+        );
+    }
+    Ok(changes)
+}
+
+/// The `force` flag for removing `worktree_dir`: with changes (see
+/// [`uncommitted_changes`]) and no `--force`, ask on a TTY and fail otherwise.
+/// `None` means the user declined.
+fn confirm_force_if_dirty(
+    worktree_dir: &Path,
+    generated: &[PathBuf],
+    ignore: &[String],
+    companions: &[meta::Companion],
+    force: bool,
+) -> Result<Option<bool>> {
+    if force {
+        return Ok(Some(true));
+    }
+    let changes = uncommitted_changes(worktree_dir, generated, ignore, companions)?;
+    if changes.is_empty() {
+        return Ok(Some(false));
+    }
+    if !exec::can_prompt() {
+        return Err(PcError::ForceRequired(format!(
+            "Worktree has uncommitted changes: {} (use --force to remove anyway)",
+            worktree_dir.display()
+        ))
+        .into());
+    }
+    println!("{}", tr(Msg::WorktreeHasLocalChanges));
+    for path in &changes {
+        println!("  {}", path.display());
+    }
+    let ok = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(trf(Msg::ConfirmForceRemove, &[&worktree_dir.display()]))
+        .default(false)
+        .interact()
+        .context("Prompt failed")?;
+    Ok(ok.then_some(true))
+}
+
+/// The `--force` to pass to `git worktree remove` once [`uncommitted_changes`] found
+/// nothing (or the user forced it). Unchanged `generated` files and `ignore` matches
+/// still look like changes to git; they go with the worktree, so an agent that is not
+/// removed after all keeps them.
+fn removal_force(force: bool, generated: &[PathBuf], ignore: &[String]) -> bool {
+    force || !generated.is_empty() || !ignore.is_empty()
+}
+
+/// Remove an existing worktree for `pc new --recreate`, keeping its branch.
+/// Refuses dirty worktrees unless `force` is set.
+fn remove_for_recreate(
+    repo_root: &Path,
+    agent_name: &str,
+    worktree_dir: &Path,
+    force: bool,
+    ignore: &[String],
+) -> Result<()> {
+    let worktree_dir = &std::fs::canonicalize(worktree_dir)
+        .with_context(|| format!("Failed to resolve {}", worktree_dir.display()))?;
+    let recorded = meta::read_agent_meta(agent_name)?;
+    let generated = unchanged_generated_files(recorded.as_ref(), worktree_dir)?;
+    let companions = recorded
+        .as_ref()
+        .map(|m| m.companions.clone())
+        .unwrap_or_default();
+    if !force && !uncommitted_changes(worktree_dir, &generated, ignore, &companions)?.is_empty() {
+        return Err(PcError::ForceRequired(format!(
+            "Existing worktree has uncommitted changes: {} (use --force to recreate anyway)",
+            worktree_dir.display()
+        ))
+        .into());
+    }
+    let lock_reason = unlock_if_locked(repo_root, worktree_dir)?;
+    let keep_agent = || {
+        relock(repo_root, worktree_dir, lock_reason.as_deref());
+        forget_companions(agent_name, recorded.as_ref());
+    };
+    remove_recorded_companions(&companions, force).inspect_err(|_| keep_agent())?;
+    let removed = git::worktree_remove(
+        repo_root,
+        worktree_dir,
+        removal_force(force, &generated, ignore),
+    )
+    .inspect_err(|_| keep_agent())?;
+    if !removed {
+        keep_agent();
+        bail!("Worktree not removed: {}", worktree_dir.display());
+    }
+    Ok(())
+}
+
+/// `git worktree remove` refuses locked worktrees; removing an agent is an explicit
+/// request, so drop the lock first. Returns the lock reason if there was a lock, for
+/// [`relock`] when the removal does not happen after all.
+fn unlock_if_locked(repo_root: &Path, worktree_dir: &Path) -> Result<Option<String>> {
+    let reason = git::worktree_entry_for_path(repo_root, worktree_dir)?.and_then(|e| e.locked);
+    if reason.is_some() {
+        git::worktree_unlock(repo_root, worktree_dir)?;
+        info!("Unlocked worktree: {}", worktree_dir.display());
+    }
+    Ok(reason)
+}
+
+/// Restore a lock dropped by [`unlock_if_locked`]; failures are only reported.
+fn relock(repo_root: &Path, worktree_dir: &Path, reason: Option<&str>) {
+    let Some(reason) = reason else {
+        return;
+    };
+    if let Err(e) = git::worktree_lock(repo_root, worktree_dir, reason) {
+        eprintln!(
+            "Warning: failed to lock {} again: {e:#}",
+            worktree_dir.display()
+        );
+    }
+}
+
 #[derive(Debug, Clone)]
 struct SelectedWorktree {
     path: PathBuf,
@@ -381,24 +1548,26 @@ fn select_worktree_to_remove_tui(
     worktree_base_dir: &Path,
 ) -> Result<Option<SelectedWorktree>> {
     if !dialoguer::console::Term::stdout().is_term() {
-        bail!("No worktree specified and no TTY available. Pass a branch name: `pc rm <branch>`.");
+        bail_usage!(
+            "No worktree specified and no TTY available. Pass a branch name: `pc rm <branch>`."
+        );
     }
 
+    let worktrees = git::worktrees(repo_root)?;
     let repo_root = std::fs::canonicalize(repo_root).unwrap_or_else(|_| repo_root.to_path_buf());
     let base = std::fs::canonicalize(worktree_base_dir)
         .unwrap_or_else(|_| worktree_base_dir.to_path_buf());
 
-    let worktrees = git::worktrees()?;
     let mut candidates: Vec<git::WorktreeEntry> = worktrees
-        .into_iter()
+        .iter()
         .filter(|e| {
             let p = std::fs::canonicalize(&e.path).unwrap_or_else(|_| e.path.clone());
             p != repo_root && p.starts_with(&base)
         })
+        .cloned()
         .collect();
 
     if candidates.is_empty() {
-        let worktrees = git::worktrees()?;
         candidates = worktrees
             .into_iter()
             .filter(|e| {
@@ -409,7 +1578,7 @@ fn select_worktree_to_remove_tui(
     }
 
     if candidates.is_empty() {
-        bail!("No removable worktrees found in this repository");
+        bail_git_state!("No removable worktrees found in this repository");
     }
 
     candidates.sort_by(|a, b| a.path.cmp(&b.path));
@@ -427,7 +1596,7 @@ fn select_worktree_to_remove_tui(
         .collect();
 
     let selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select worktree to remove")
+        .with_prompt(tr(Msg::SelectWorktreeToRemove))
         .items(&items)
         .default(0)
         .interact_opt()
@@ -472,10 +1641,13 @@ fn confirm_double_rm(
     agent_name: &str,
 ) -> Result<bool> {
     let label = branch_name.unwrap_or(agent_name);
-    let mut prompt = format!("Remove worktree: {}", worktree_dir.display());
-    if let Some(b) = branch_name {
-        prompt.push_str(&format!(" (branch: {b})"));
-    }
+    let prompt = match branch_name {
+        Some(b) => trf(
+            Msg::ConfirmRemoveWorktreeOnBranch,
+            &[&worktree_dir.display(), &b],
+        ),
+        None => trf(Msg::ConfirmRemoveWorktree, &[&worktree_dir.display()]),
+    };
 
     let ok = Confirm::with_theme(&ColorfulTheme::default())
         .with_prompt(prompt)
@@ -487,7 +1659,7 @@ fn confirm_double_rm(
     }
 
     let typed = Input::<String>::with_theme(&ColorfulTheme::default())
-        .with_prompt(format!("Type '{label}' to confirm"))
+        .with_prompt(trf(Msg::TypeToConfirm, &[&label]))
         .default("".to_string())
         .interact_text()
         .context("Prompt failed")?;
@@ -495,15 +1667,20 @@ fn confirm_double_rm(
     Ok(typed.trim() == label)
 }
 
+/// Precedence: `--base-dir`, then `AGENT_WORKTREE_BASE_DIR`, then `.pc.toml`,
+/// then `<repo>/../<repo-name>-agents`.
 fn resolve_worktree_base_dir(
     repo_root: &Path,
     repo_name: &str,
     arg_base_dir: Option<PathBuf>,
+    config_base_dir: Option<PathBuf>,
 ) -> Result<PathBuf> {
     Ok(if let Some(d) = arg_base_dir {
         d
     } else if let Some(env) = std::env::var_os("AGENT_WORKTREE_BASE_DIR") {
         PathBuf::from(env)
+    } else if let Some(d) = config_base_dir {
+        d
     } else {
         let parent = repo_root
             .parent()
@@ -512,20 +1689,224 @@ fn resolve_worktree_base_dir(
     })
 }
 
+const COMPANIONS_DIR: &str = ".pc-companions";
+
+/// A validated `--companion REPO#BRANCH`: canonical repo, branch, and its directory name
+/// under [`COMPANIONS_DIR`].
+struct CompanionSpec {
+    repo: PathBuf,
+    branch: String,
+    name: String,
+}
+
+/// Check every companion before anything is created: the repo exists, has the branch,
+/// and its directory name is unique.
+fn resolve_companions(specs: &[(PathBuf, String)]) -> Result<Vec<CompanionSpec>> {
+    let mut out: Vec<CompanionSpec> = Vec::new();
+    for (repo, branch) in specs {
+        let repo = std::fs::canonicalize(repo)
+            .with_context(|| format!("Companion repository not found: {}", repo.display()))
+            .or_usage()?;
+        let name = repo
+            .file_name()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| anyhow!("Failed to get repo name from path: {}", repo.display()))?
+            .to_string();
+        if !git::branch_exists_in(&repo, branch) {
+            bail_git_state!(
+                "Companion branch not found: {branch} (in {})",
+                repo.display()
+            );
+        }
+        if out.iter().any(|c| c.name == name) {
+            bail_usage!("Two companions would both be checked out as {COMPANIONS_DIR}/{name}");
+        }
+        out.push(CompanionSpec {
+            repo,
+            branch: branch.clone(),
+            name,
+        });
+    }
+    Ok(out)
+}
+
+/// Add the companion worktrees, registering the removal of each one with `rollback`
+/// before it is created (Ctrl-C may land while `git worktree add` is still running).
+fn add_companions(
+    worktree_dir: &Path,
+    specs: &[CompanionSpec],
+    rollback: &interrupt::Guard,
+) -> Result<Vec<meta::Companion>> {
+    git::ensure_exclude(worktree_dir, &format!("{COMPANIONS_DIR}/"))?;
+    let mut added = Vec::new();
+    for spec in specs {
+        let dir = worktree_dir.join(COMPANIONS_DIR).join(&spec.name);
+        let companion = meta::Companion {
+            repo: spec.repo.clone(),
+            branch: spec.branch.clone(),
+            worktree_path: dir.clone(),
+        };
+        {
+            let companion = companion.clone();
+            rollback.push(move || remove_companions(&[companion], true));
+        }
+        git::worktree_add_in(&spec.repo, &dir, &spec.branch)?;
+        info!("Companion: {} ({})", dir.display(), spec.branch);
+        added.push(companion);
+    }
+    Ok(added)
+}
+
+/// Best-effort removal used on rollback paths; failures are only reported.
+fn remove_companions(companions: &[meta::Companion], force: bool) {
+    for c in companions {
+        if !c.worktree_path.exists() {
+            continue;
+        }
+        if let Err(e) = git::worktree_remove_in(&c.repo, &c.worktree_path, force) {
+            eprintln!(
+                "Warning: failed to remove companion worktree {}: {e:#}",
+                c.worktree_path.display()
+            );
+        }
+    }
+}
+
+/// Remove an agent's recorded companion worktrees. Callers check first (see
+/// [`uncommitted_changes`]) that the agent worktree itself is going to be removed.
+fn remove_recorded_companions(companions: &[meta::Companion], force: bool) -> Result<()> {
+    for c in companions {
+        if !c.worktree_path.exists() {
+            continue;
+        }
+        git::worktree_remove_in(&c.repo, &c.worktree_path, force)?;
+        info!("Removed companion worktree {}", c.worktree_path.display());
+    }
+    Ok(())
+}
+
+/// The agent worktree survived a removal attempt: its metadata should no longer list
+/// companions that are already gone.
+fn forget_companions(agent_name: &str, recorded: Option<&AgentMeta>) {
+    let Some(m) = recorded else {
+        return;
+    };
+    let kept: Vec<meta::Companion> = m
+        .companions
+        .iter()
+        .filter(|c| c.worktree_path.exists())
+        .cloned()
+        .collect();
+    if kept.len() == m.companions.len() {
+        return;
+    }
+    let updated = AgentMeta {
+        companions: kept,
+        ..m.clone()
+    };
+    if let Err(e) = meta::write_agent_meta(agent_name, updated) {
+        eprintln!("Warning: failed to update metadata of agent {agent_name}: {e:#}");
+    }
+}
+
+const ENV_EXAMPLE_FILE: &str = ".env.example";
+const ENV_FILE: &str = ".env";
+
+/// Copy `.env.example` from the commit the worktree starts at (its HEAD, which is the
+/// branch tip rather than the base when the branch already existed) to `.env`. Returns
+/// the generated file (relative), or nothing if the worktree already has a `.env` or the
+/// commit has no example.
+fn write_env_from_base(worktree_dir: &Path, branch_name: &str) -> Result<Option<PathBuf>> {
+    let target = worktree_dir.join(ENV_FILE);
+    if target.exists() {
+        info!("Kept existing {ENV_FILE}");
+        return Ok(None);
+    }
+    let Some(contents) = git::show_file(worktree_dir, "HEAD", ENV_EXAMPLE_FILE)? else {
+        eprintln!("Warning: {branch_name}:{ENV_EXAMPLE_FILE} not found; no {ENV_FILE} written");
+        return Ok(None);
+    };
+    std::fs::write(&target, contents)
+        .with_context(|| format!("Failed to write {}", target.display()))?;
+    info!("Wrote {ENV_FILE} from {branch_name}:{ENV_EXAMPLE_FILE}");
+    Ok(Some(PathBuf::from(ENV_FILE)))
+}
+
+/// `worktree_path_template` (default `{agent}`) expanded below the base dir.
+fn agent_worktree_dir(
+    worktree_base_dir: &Path,
+    template: Option<&str>,
+    repo_name: &str,
+    agent_name: &str,
+    branch_name: &str,
+) -> Result<PathBuf> {
+    let Some(template) = template else {
+        return Ok(worktree_base_dir.join(agent_name));
+    };
+    let (year, month, day) = meta::today_utc();
+    let rel = pc_cli::paths::expand_template(template, |name| match name {
+        "repo" => Some(repo_name.to_string()),
+        "agent" => Some(agent_name.to_string()),
+        "branch_sanitized" => derive_agent_name_from_branch(branch_name).ok(),
+        "date" => Some(format!("{year:04}-{month:02}-{day:02}")),
+        "month" => Some(format!("{year:04}-{month:02}")),
+        "user" => std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .ok(),
+        _ => None,
+    })
+    .map_err(|e| PcError::Usage(format!("Invalid worktree_path_template: {e}")))?;
+    Ok(worktree_base_dir.join(rel))
+}
+
+/// Remove directories left empty between a removed worktree and the base dir
+/// (e.g. `{month}/{user}` levels of `worktree_path_template`).
+fn remove_empty_parents(worktree_dir: &Path, worktree_base_dir: &Path) {
+    let base = std::fs::canonicalize(worktree_base_dir)
+        .unwrap_or_else(|_| worktree_base_dir.to_path_buf());
+    let mut dir = worktree_dir.parent();
+    while let Some(d) = dir {
+        if d == base || !d.starts_with(&base) || std::fs::remove_dir(d).is_err() {
+            break;
+        }
+        dir = d.parent();
+    }
+}
+
+/// A base dir inside the repository would make every new worktree show up as
+/// untracked files of the main worktree.
+fn ensure_base_dir_outside_repo(repo_root: &Path, worktree_base_dir: &Path) -> Result<()> {
+    let repo_root = std::fs::canonicalize(repo_root)
+        .with_context(|| format!("Failed to resolve {}", repo_root.display()))?;
+    let base = std::fs::canonicalize(worktree_base_dir)
+        .with_context(|| format!("Failed to resolve {}", worktree_base_dir.display()))?;
+    if base.starts_with(&repo_root) {
+        bail_usage!("Worktree base dir must be outside the repository: {} (repo: {}). Use --base-dir or AGENT_WORKTREE_BASE_DIR to pick another location.",
+            base.display(),
+            repo_root.display()
+        );
+    }
+    Ok(())
+}
+
+/// Undo `pc new` up to and including the worktree: only what actually exists is removed,
+/// and failures are reported rather than returned (this runs on error and signal paths).
 fn rollback_failed_agent_new(
     repo_root: &Path,
     agent_name: &str,
     worktree_dir: &Path,
     branch_name: &str,
     created_branch: bool,
-) -> Result<()> {
-    if let Err(e) = git::worktree_remove(worktree_dir, true) {
-        eprintln!(
-            "Warning: git worktree remove --force failed during rollback for {}: {e:#}",
-            worktree_dir.display()
-        );
+) {
+    if worktree_dir.exists() {
+        if let Err(e) = git::worktree_remove(repo_root, worktree_dir, true) {
+            eprintln!(
+                "Warning: git worktree remove --force failed during rollback for {}: {e:#}",
+                worktree_dir.display()
+            );
+        }
     }
-    if created_branch {
+    if created_branch && git::branch_exists_in(repo_root, branch_name) {
         if let Err(e) = git::branch_delete_force(repo_root, branch_name) {
             eprintln!(
                 "Warning: git branch -D failed during rollback for {}: {e:#}",
@@ -539,40 +1920,59 @@ fn rollback_failed_agent_new(
             agent_name
         );
     }
-    Ok(())
 }
 
-fn select_base_branch_tui() -> Result<Option<String>> {
+fn select_base_branch_tui(repo_root: &Path) -> Result<Option<String>> {
     if !dialoguer::console::Term::stdout().is_term() {
-        bail!("Interactive base selection requires a TTY");
+        bail_usage!("Interactive base selection requires a TTY");
     }
 
-    let branches = git::local_branches_by_recent()?;
-    if branches.is_empty() {
-        bail!("No local branches found");
-    }
+    // The first entry toggles remote-tracking branches in and out of the list.
+    let mut show_remotes = false;
+    loop {
+        let branches = git::branches_by_recent(repo_root, show_remotes)?;
+        if branches.is_empty() && !show_remotes {
+            bail_git_state!("No local branches found");
+        }
 
-    let items: Vec<String> = branches
-        .iter()
-        .map(|b| format!("{}  ({})", b.name, b.committer_date))
-        .collect();
-    let selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select base branch")
-        .items(&items)
-        .default(0)
-        .interact_opt()
-        .context("TUI selection failed")?;
-    Ok(selection.map(|idx| branches[idx].name.clone()))
+        let toggle = if show_remotes {
+            tr(Msg::HideRemoteBranches)
+        } else {
+            tr(Msg::ShowRemoteBranches)
+        };
+        let items: Vec<String> = std::iter::once(toggle.to_string())
+            .chain(branches.iter().map(|b| {
+                if b.remote {
+                    format!("{}  ({})  [remote]", b.name, b.committer_date)
+                } else {
+                    format!("{}  ({})", b.name, b.committer_date)
+                }
+            }))
+            .collect();
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt(tr(Msg::SelectBaseBranch))
+            .items(&items)
+            .default(1.min(branches.len()))
+            .interact_opt()
+            .context("TUI selection failed")?;
+        match selection {
+            None => return Ok(None),
+            Some(0) => show_remotes = !show_remotes,
+            Some(idx) => return Ok(Some(branches[idx - 1].name.clone())),
+        }
+    }
 }
 
-fn select_target_branch_tui() -> Result<Option<String>> {
+fn select_target_branch_tui(repo_root: &Path) -> Result<Option<String>> {
     if !dialoguer::console::Term::stdout().is_term() {
-        bail!("No branch specified and no TTY available. Pass a branch name: `pc new <branch>`.");
+        bail_usage!(
+            "No branch specified and no TTY available. Pass a branch name: `pc new <branch>`."
+        );
     }
 
-    let branches = git::local_branches_by_recent()?;
+    let branches = git::branches_by_recent(repo_root, false)?;
     if branches.is_empty() {
-        bail!("No local branches found");
+        bail_git_state!("No local branches found");
     }
 
     let items: Vec<String> = branches
@@ -580,10 +1980,97 @@ fn select_target_branch_tui() -> Result<Option<String>> {
         .map(|b| format!("{}  ({})", b.name, b.committer_date))
         .collect();
     let selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select branch to open as worktree")
+        .with_prompt(tr(Msg::SelectBranchToOpen))
         .items(&items)
         .default(0)
         .interact_opt()
         .context("TUI selection failed")?;
     Ok(selection.map(|idx| branches[idx].name.clone()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(name: &str, created_at: Option<u64>) -> (String, AgentMeta) {
+        (
+            name.to_string(),
+            AgentMeta {
+                branch_name: Some(name.to_string()),
+                created_at,
+                ..AgentMeta::default()
+            },
+        )
+    }
+
+    #[test]
+    fn sort_agents_by_recent_puts_newest_first_and_legacy_last() {
+        let mut agents = vec![
+            agent("legacy-b", None),
+            agent("old", Some(10)),
+            agent("legacy-a", None),
+            agent("new", Some(20)),
+        ];
+        sort_agents_by_recent(&mut agents);
+        let names: Vec<&str> = agents.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["new", "old", "legacy-a", "legacy-b"]);
+    }
+
+    fn parse_new_args(argv: &[&str]) -> AgentNewArgs {
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct Wrapper {
+            #[command(flatten)]
+            args: AgentNewArgs,
+        }
+        Wrapper::parse_from(std::iter::once("pc").chain(argv.iter().copied())).args
+    }
+
+    fn profile() -> config::AgentProfile {
+        config::AgentProfile {
+            base: Some("develop".to_string()),
+            template_dir: Some(PathBuf::from("/templates/py")),
+            labels: [
+                ("team".to_string(), "core".to_string()),
+                ("kind".to_string(), "backend".to_string()),
+            ]
+            .into(),
+            no_open: true,
+            no_preflight: false,
+        }
+    }
+
+    #[test]
+    fn apply_profile_fills_unset_options() {
+        let mut args = parse_new_args(&["feat/a"]);
+        apply_profile(&mut args, &profile());
+        assert_eq!(args.base.as_deref(), Some("develop"));
+        assert_eq!(args.template_dir, Some(PathBuf::from("/templates/py")));
+        assert!(args.no_open);
+        assert!(!args.no_preflight);
+    }
+
+    #[test]
+    fn apply_profile_lets_cli_flags_win() {
+        let mut args = parse_new_args(&[
+            "feat/a",
+            "--base",
+            "main",
+            "--template-dir",
+            "/templates/rust",
+            "--label",
+            "team=web",
+        ]);
+        apply_profile(&mut args, &profile());
+        assert_eq!(args.base.as_deref(), Some("main"));
+        assert_eq!(args.template_dir, Some(PathBuf::from("/templates/rust")));
+        let labels: std::collections::BTreeMap<_, _> = args.labels.into_iter().collect();
+        assert_eq!(labels["team"], "web");
+        assert_eq!(labels["kind"], "backend");
+
+        let mut args = parse_new_args(&["feat/a", "--select-base"]);
+        apply_profile(&mut args, &profile());
+        assert_eq!(args.base, None);
+    }
+}
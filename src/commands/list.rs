@@ -0,0 +1,174 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::cli::{ListArgs, ListFormat};
+use crate::exec;
+use crate::git;
+use crate::meta::{self, AgentMeta};
+
+#[derive(Debug, Serialize)]
+struct ListedAgent<'a> {
+    agent_name: &'a str,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    locked: bool,
+    #[serde(flatten)]
+    meta: &'a AgentMeta,
+}
+
+pub(crate) fn cmd_list(args: ListArgs) -> Result<()> {
+    exec::ensure_in_path("git")?;
+    let repo_root = git::repo_root()?;
+    let agents: Vec<(String, AgentMeta)> = meta::list_agent_metas()?
+        .into_iter()
+        .filter(|(_, m)| matches_filters(m, args.branch.as_deref(), &args.labels))
+        .collect();
+    warn_missing_branches(&repo_root, &agents)?;
+    let worktrees = git::worktrees(&repo_root)?;
+    let is_locked = |m: &AgentMeta| {
+        m.worktree_path
+            .as_deref()
+            .and_then(|p| git::find_worktree_for_path(&worktrees, p))
+            .is_some_and(|e| e.locked.is_some())
+    };
+
+    let format = if args.json {
+        ListFormat::Json
+    } else {
+        args.format
+    };
+    if format == ListFormat::Json {
+        let listed: Vec<ListedAgent<'_>> = agents
+            .iter()
+            .map(|(name, meta)| ListedAgent {
+                agent_name: name,
+                locked: is_locked(meta),
+                meta,
+            })
+            .collect();
+        let text = serde_json::to_string_pretty(&listed).context("Failed to encode JSON")?;
+        println!("{text}");
+        return Ok(());
+    }
+
+    let rows: Vec<[String; 4]> = agents
+        .iter()
+        .map(|(name, m)| {
+            let labels: Vec<String> = m.labels.iter().map(|(k, v)| format!("{k}={v}")).collect();
+            [
+                name.clone(),
+                m.branch_name.clone().unwrap_or_else(|| "?".to_string()),
+                m.worktree_path
+                    .as_deref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| "?".to_string()),
+                labels.join(","),
+            ]
+        })
+        .collect();
+    if format == ListFormat::Plain {
+        for row in &rows {
+            println!("{}", row.join("\t"));
+        }
+        return Ok(());
+    }
+
+    let name_w = rows.iter().map(|r| r[0].len()).max().unwrap_or(0);
+    let branch_w = rows.iter().map(|r| r[1].len()).max().unwrap_or(0);
+    for ([name, branch, path, labels], (_, m)) in rows.iter().zip(&agents) {
+        let lock = if is_locked(m) { "  [locked]" } else { "" };
+        let line = format!("{name:<name_w$}  {branch:<branch_w$}  {path}{lock}  {labels}");
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+/// Agents whose branch was deleted behind pc's back (the worktree may still exist).
+fn warn_missing_branches(repo_root: &Path, agents: &[(String, AgentMeta)]) -> Result<()> {
+    let refs: Vec<String> = agents
+        .iter()
+        .filter_map(|(_, m)| m.branch_name.as_deref())
+        .map(|b| format!("refs/heads/{b}"))
+        .collect();
+    if refs.is_empty() {
+        return Ok(());
+    }
+    let existing = git::existing_refs(repo_root, &refs)?;
+    for (name, m) in agents {
+        if let Some(branch) = m.branch_name.as_deref() {
+            if !existing.contains(&format!("refs/heads/{branch}")) {
+                eprintln!("Warning: branch {branch} of agent {name} no longer exists");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// All filters must match (AND). `branch` is a glob (`*` and `?`); agents without
+/// a recorded branch never match a branch filter.
+fn matches_filters(m: &AgentMeta, branch: Option<&str>, labels: &[(String, String)]) -> bool {
+    if let Some(pattern) = branch {
+        match m.branch_name.as_deref() {
+            Some(b) if glob_match(pattern, b) => {}
+            _ => return false,
+        }
+    }
+    labels
+        .iter()
+        .all(|(k, v)| m.labels.get(k).is_some_and(|actual| actual == v))
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_match_supports_star_and_question_mark() {
+        assert!(glob_match("feat/*", "feat/a"));
+        assert!(glob_match("feat/*", "feat/a/b"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("fix-?", "fix-1"));
+        assert!(!glob_match("fix-?", "fix-12"));
+        assert!(!glob_match("feat/*", "fix/a"));
+        assert!(glob_match("*-wip", "feat/x-wip"));
+    }
+
+    #[test]
+    fn filters_combine_with_and() {
+        let m = AgentMeta {
+            branch_name: Some("feat/a".to_string()),
+            labels: [("team".to_string(), "core".to_string())].into(),
+            ..AgentMeta::default()
+        };
+        let team_core = [("team".to_string(), "core".to_string())];
+        let team_web = [("team".to_string(), "web".to_string())];
+        assert!(matches_filters(&m, Some("feat/*"), &team_core));
+        assert!(!matches_filters(&m, Some("fix/*"), &team_core));
+        assert!(!matches_filters(&m, Some("feat/*"), &team_web));
+        assert!(matches_filters(&m, None, &[]));
+    }
+}
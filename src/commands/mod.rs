@@ -1 +1,4 @@
 pub(crate) mod agent;
+pub(crate) mod list;
+pub(crate) mod serve;
+pub(crate) mod shell;
@@ -0,0 +1,292 @@
+//! `pc serve`: a read-only JSON view of the recorded agents for dashboards.
+//!
+//! A background thread refreshes a snapshot of the agent metadata and worktree state
+//! every [`REFRESH_INTERVAL`]; requests are answered from that snapshot, each connection
+//! on its own short-lived thread, so polling clients never spawn git. Only
+//! `GET /health`, `GET /agents` and `GET /agents/<name>` exist; nothing here mutates a
+//! repository.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::cli::ServeArgs;
+use crate::error::bail_usage;
+use crate::exec;
+use crate::git;
+use crate::meta::{self, AgentMeta};
+use crate::output::info;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+/// A client that stalls this long while sending its request (or reading the answer) is
+/// dropped.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// Request line and headers together; anything longer is cut off.
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+/// Connections handled at once; further clients get a 503 until one finishes.
+const MAX_CONNECTIONS: usize = 32;
+
+static ACTIVE_CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, Serialize)]
+struct AgentStatus {
+    agent_name: String,
+    /// The recorded worktree directory is still there.
+    worktree_exists: bool,
+    /// The recorded branch still exists.
+    branch_exists: bool,
+    locked: bool,
+    #[serde(flatten)]
+    meta: AgentMeta,
+}
+
+#[derive(Debug, Default)]
+struct Snapshot {
+    agents: Vec<AgentStatus>,
+    refreshed: Option<Instant>,
+    error: Option<String>,
+}
+
+type Shared = Arc<RwLock<Snapshot>>;
+
+pub(crate) fn cmd_serve(args: ServeArgs) -> Result<()> {
+    exec::ensure_in_path("git")?;
+    let repo_root = git::repo_root()?;
+    let shared: Shared = Arc::default();
+    refresh(&repo_root, &shared);
+    {
+        let shared = Arc::clone(&shared);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(REFRESH_INTERVAL);
+            refresh(&repo_root, &shared);
+        });
+    }
+
+    if let Some(port) = args.port {
+        on_terminate(None)?;
+        let listener = std::net::TcpListener::bind(("127.0.0.1", port))
+            .with_context(|| format!("Failed to listen on 127.0.0.1:{port}"))?;
+        let addr = listener
+            .local_addr()
+            .context("Failed to read listen address")?;
+        // On stderr even with --quiet: with `--port 0` this is the only way to learn the port.
+        eprintln!("Listening on http://{addr}");
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+                    let _ = stream.set_write_timeout(Some(READ_TIMEOUT));
+                    spawn_handler(stream, &shared);
+                }
+                Err(e) => eprintln!("Warning: failed to accept connection: {e}"),
+            }
+        }
+        return Ok(());
+    }
+
+    let socket = match args.socket {
+        Some(p) => p,
+        None => meta::git_path("pc/pc.sock")?,
+    };
+    serve_unix(&socket, &shared)
+}
+
+#[cfg(unix)]
+fn serve_unix(socket: &Path, shared: &Shared) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            bail_usage!(
+                "Another server is already listening on {}",
+                socket.display()
+            );
+        }
+        // Left behind by a server that did not shut down cleanly.
+        std::fs::remove_file(socket)
+            .with_context(|| format!("Failed to remove stale socket {}", socket.display()))?;
+    }
+    if let Some(parent) = socket.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    on_terminate(Some(socket.to_path_buf()))?;
+    let listener = UnixListener::bind(socket)
+        .with_context(|| format!("Failed to listen on {}", socket.display()))?;
+    // The socket file permissions are the only access control.
+    std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))
+        .with_context(|| format!("Failed to set permissions on {}", socket.display()))?;
+    info!("Listening on {}", socket.display());
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+                let _ = stream.set_write_timeout(Some(READ_TIMEOUT));
+                spawn_handler(stream, shared);
+            }
+            Err(e) => eprintln!("Warning: failed to accept connection: {e}"),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn serve_unix(_socket: &Path, _shared: &Shared) -> Result<()> {
+    bail_usage!("Unix sockets are not supported on this platform; use --port");
+}
+
+/// Exit (status 0) on SIGTERM/SIGINT/SIGHUP, removing the socket file first.
+fn on_terminate(socket: Option<PathBuf>) -> Result<()> {
+    ctrlc::set_handler(move || {
+        if let Some(socket) = socket.as_deref() {
+            let _ = std::fs::remove_file(socket);
+        }
+        std::process::exit(0);
+    })
+    .context("Failed to install signal handler")
+}
+
+fn refresh(repo_root: &Path, shared: &Shared) {
+    let collected = collect_agents(repo_root);
+    let mut snapshot = shared.write().unwrap_or_else(|e| e.into_inner());
+    match collected {
+        Ok(agents) => {
+            snapshot.agents = agents;
+            snapshot.error = None;
+        }
+        // Keep serving the last good snapshot; /health reports the problem.
+        Err(e) => snapshot.error = Some(format!("{e:#}")),
+    }
+    snapshot.refreshed = Some(Instant::now());
+}
+
+fn collect_agents(repo_root: &Path) -> Result<Vec<AgentStatus>> {
+    let agents = meta::list_agent_metas()?;
+    let worktrees = git::worktrees(repo_root)?;
+    let refs: Vec<String> = agents
+        .iter()
+        .filter_map(|(_, m)| m.branch_name.as_deref())
+        .map(|b| format!("refs/heads/{b}"))
+        .collect();
+    let existing = if refs.is_empty() {
+        Default::default()
+    } else {
+        git::existing_refs(repo_root, &refs)?
+    };
+    Ok(agents
+        .into_iter()
+        .map(|(agent_name, meta)| {
+            let entry = meta
+                .worktree_path
+                .as_deref()
+                .and_then(|p| git::find_worktree_for_path(&worktrees, p));
+            AgentStatus {
+                agent_name,
+                worktree_exists: meta.worktree_path.as_deref().is_some_and(Path::is_dir),
+                branch_exists: meta
+                    .branch_name
+                    .as_deref()
+                    .is_some_and(|b| existing.contains(&format!("refs/heads/{b}"))),
+                locked: entry.is_some_and(|e| e.locked.is_some()),
+                meta,
+            }
+        })
+        .collect())
+}
+
+/// Answer `stream` on its own thread, so a slow client (up to [`READ_TIMEOUT`]) does not
+/// hold up the others. At most [`MAX_CONNECTIONS`] are handled at once.
+fn spawn_handler<S: Read + Write + Send + 'static>(mut stream: S, shared: &Shared) {
+    if ACTIVE_CONNECTIONS.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+        ACTIVE_CONNECTIONS.fetch_sub(1, Ordering::SeqCst);
+        let (status, body) = error("503 Service Unavailable", "too many connections");
+        write_response(&mut stream, status, &body);
+        return;
+    }
+    let shared = Arc::clone(shared);
+    let spawned = std::thread::Builder::new()
+        .name("pc-serve-conn".into())
+        .spawn(move || {
+            handle(stream, &shared);
+            ACTIVE_CONNECTIONS.fetch_sub(1, Ordering::SeqCst);
+        });
+    if let Err(e) = spawned {
+        ACTIVE_CONNECTIONS.fetch_sub(1, Ordering::SeqCst);
+        eprintln!("Warning: failed to start connection handler: {e}");
+    }
+}
+
+fn handle<S: Read + Write>(mut stream: S, shared: &Shared) {
+    let mut request_line = String::new();
+    {
+        let mut reader = BufReader::new((&mut stream).take(MAX_REQUEST_BYTES));
+        if reader.read_line(&mut request_line).is_err() {
+            return;
+        }
+        // Drain the headers; no request here has a body.
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match reader.read_line(&mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) if line.trim_end().is_empty() => break,
+                Ok(_) => {}
+            }
+        }
+    }
+    let (status, body) = respond(&request_line, shared);
+    write_response(&mut stream, status, &body);
+}
+
+fn write_response(stream: &mut impl Write, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
+
+fn respond(request_line: &str, shared: &Shared) -> (&'static str, String) {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return error("400 Bad Request", "malformed request line");
+    };
+    if method != "GET" {
+        return error("405 Method Not Allowed", "only GET is supported");
+    }
+    let path = target.split('?').next().unwrap_or(target);
+    let snapshot = shared.read().unwrap_or_else(|e| e.into_inner());
+    match path.trim_end_matches('/') {
+        "/health" => ok(&serde_json::json!({
+            "status": if snapshot.error.is_some() { "degraded" } else { "ok" },
+            "agents": snapshot.agents.len(),
+            "refreshed_ms_ago": snapshot.refreshed.map(|t| t.elapsed().as_millis() as u64),
+            "error": snapshot.error,
+        })),
+        "/agents" => ok(&snapshot.agents),
+        p => match p.strip_prefix("/agents/") {
+            Some(name) => match snapshot.agents.iter().find(|a| a.agent_name == name) {
+                Some(agent) => ok(agent),
+                None => error("404 Not Found", &format!("no agent named {name}")),
+            },
+            None => error("404 Not Found", &format!("no such endpoint: {path}")),
+        },
+    }
+}
+
+fn ok(value: &impl Serialize) -> (&'static str, String) {
+    match serde_json::to_string(value) {
+        Ok(body) => ("200 OK", body),
+        Err(e) => error("500 Internal Server Error", &e.to_string()),
+    }
+}
+
+fn error(status: &'static str, message: &str) -> (&'static str, String) {
+    (status, serde_json::json!({ "error": message }).to_string())
+}
@@ -0,0 +1,43 @@
+use anyhow::Result;
+
+use crate::cli::{Shell, ShellInitArgs};
+
+const BASH: &str = r#"pca() {
+    local dir
+    dir="$(command pc path "$@")" && cd "$dir"
+}
+_pca_complete() {
+    COMPREPLY=($(compgen -W "$(command pc list 2>/dev/null | awk '{print $1}')" -- "${COMP_WORDS[COMP_CWORD]}"))
+}
+complete -F _pca_complete pca
+"#;
+
+const ZSH: &str = r#"pca() {
+    local dir
+    dir="$(command pc path "$@")" && cd "$dir"
+}
+_pca() {
+    compadd -- ${(f)"$(command pc list 2>/dev/null | awk '{print $1}')"}
+}
+if (( $+functions[compdef] )); then
+    compdef _pca pca
+fi
+"#;
+
+const FISH: &str = r#"function pca
+    set -l dir (command pc path $argv); and cd $dir
+end
+complete -c pca -f -a '(command pc list 2>/dev/null | string replace -r "\s.*" "")'
+"#;
+
+/// Print a `pca <agent>` function that cds into the agent's worktree, with
+/// agent-name completion.
+pub(crate) fn cmd_shell_init(args: ShellInitArgs) -> Result<()> {
+    let script = match args.shell {
+        Shell::Bash => BASH,
+        Shell::Zsh => ZSH,
+        Shell::Fish => FISH,
+    };
+    print!("{script}");
+    Ok(())
+}
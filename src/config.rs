@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+pub(crate) const REPO_CONFIG_FILE: &str = ".pc.toml";
+
+/// Repo-local defaults from `.pc.toml`. Explicit CLI flags (and env vars) win.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RepoConfig {
+    /// Base directory for agent worktrees.
+    #[serde(default)]
+    pub(crate) base_dir: Option<PathBuf>,
+    /// Local devcontainer template directory copied into new worktrees.
+    #[serde(default)]
+    pub(crate) template_dir: Option<PathBuf>,
+    /// Layout of new worktrees below the base dir, e.g. `"{month}/{user}/{agent}"`
+    /// (default `"{agent}"`).
+    #[serde(default)]
+    pub(crate) worktree_path_template: Option<String>,
+    /// Refuse `pc new` when this many agents are already recorded.
+    #[serde(default)]
+    pub(crate) max_agents: Option<usize>,
+    /// Refuse `pc new` when the base dir's filesystem has less free space (MB).
+    #[serde(default)]
+    pub(crate) min_free_mb: Option<u64>,
+    /// Gitignore patterns for disposable build output (e.g. `".venv/"`, `"node_modules/"`,
+    /// `"target/"`) excluded before `pc rm` / `--recreate` remove a worktree, so it does
+    /// not need `--force`. Nothing is excluded unless listed here or via `--ignore`.
+    #[serde(default)]
+    pub(crate) rm_ignore: Vec<String>,
+    /// Named bundles of `pc new` options (`[agent_profile.<name>]`), selected with `--profile`.
+    #[serde(default, rename = "agent_profile")]
+    pub(crate) agent_profiles: BTreeMap<String, AgentProfile>,
+}
+
+/// A saved set of `pc new` options. Flags given on the command line override it.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct AgentProfile {
+    #[serde(default)]
+    pub(crate) base: Option<String>,
+    #[serde(default)]
+    pub(crate) template_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub(crate) labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub(crate) no_open: bool,
+    #[serde(default)]
+    pub(crate) no_preflight: bool,
+}
+
+/// Load the nearest `.pc.toml` (see [`find_repo_config`]).
+/// Relative paths inside it are resolved against the file's directory.
+pub(crate) fn load_repo_config(repo_root: &Path) -> Result<RepoConfig> {
+    match find_repo_config(repo_root)? {
+        Some(path) => read_repo_config(&path),
+        None => Ok(RepoConfig::default()),
+    }
+}
+
+/// Find the nearest `.pc.toml` walking up from the current directory to `repo_root`.
+pub(crate) fn find_repo_config(repo_root: &Path) -> Result<Option<PathBuf>> {
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let repo_root = std::fs::canonicalize(repo_root).unwrap_or_else(|_| repo_root.to_path_buf());
+    let start = std::fs::canonicalize(&cwd).unwrap_or(cwd);
+
+    let mut dir = if start.starts_with(&repo_root) {
+        Some(start.as_path())
+    } else {
+        Some(repo_root.as_path())
+    };
+    while let Some(d) = dir {
+        let path = d.join(REPO_CONFIG_FILE);
+        if path.is_file() {
+            return Ok(Some(path));
+        }
+        if d == repo_root {
+            break;
+        }
+        dir = d.parent();
+    }
+    Ok(None)
+}
+
+fn read_repo_config(path: &Path) -> Result<RepoConfig> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut config: RepoConfig =
+        toml::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let resolve = |p: PathBuf| -> Result<PathBuf> {
+        let expanded = pc_cli::paths::expand_path(&p.to_string_lossy())
+            .map_err(|e| anyhow::anyhow!("{e} (in {})", path.display()))?;
+        Ok(dir.join(expanded))
+    };
+    config.base_dir = config.base_dir.map(resolve).transpose()?;
+    config.template_dir = config.template_dir.map(resolve).transpose()?;
+    for profile in config.agent_profiles.values_mut() {
+        profile.template_dir = profile.template_dir.take().map(resolve).transpose()?;
+    }
+    Ok(config)
+}
+
+/// Write `profile` as `[agent_profile.<name>]` into the config file at `path`
+/// (created if missing), replacing any previous profile of that name. Comments and
+/// formatting elsewhere in the file are preserved.
+pub(crate) fn save_agent_profile(path: &Path, name: &str, profile: &AgentProfile) -> Result<()> {
+    let text = if path.exists() {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?
+    } else {
+        String::new()
+    };
+    let mut doc: toml_edit::DocumentMut = text
+        .parse()
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let dir = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    let mut table = toml_edit::Table::new();
+    if let Some(base) = profile.base.as_deref() {
+        table["base"] = toml_edit::value(base);
+    }
+    if let Some(template_dir) = profile.template_dir.as_deref() {
+        let shown = template_dir.strip_prefix(&dir).unwrap_or(template_dir);
+        table["template_dir"] = toml_edit::value(shown.to_string_lossy().as_ref());
+    }
+    if !profile.labels.is_empty() {
+        let mut labels = toml_edit::InlineTable::new();
+        for (k, v) in &profile.labels {
+            labels.insert(k, v.as_str().into());
+        }
+        table["labels"] = toml_edit::value(labels);
+    }
+    if profile.no_open {
+        table["no_open"] = toml_edit::value(true);
+    }
+    if profile.no_preflight {
+        table["no_preflight"] = toml_edit::value(true);
+    }
+
+    let profiles = doc
+        .entry("agent_profile")
+        .or_insert_with(|| {
+            let mut t = toml_edit::Table::new();
+            t.set_implicit(true);
+            toml_edit::Item::Table(t)
+        })
+        .as_table_mut()
+        .with_context(|| format!("agent_profile is not a table in {}", path.display()))?;
+    profiles.insert(name, toml_edit::Item::Table(table));
+
+    std::fs::write(path, doc.to_string())
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
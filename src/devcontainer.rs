@@ -0,0 +1,84 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::error::{bail_git_state, bail_usage};
+
+pub(crate) fn validate_template_dir(dir: &Path) -> Result<()> {
+    if !dir.is_dir() {
+        bail_usage!("Template dir not found: {}", dir.display());
+    }
+    if !dir.join("devcontainer.json").is_file() {
+        bail_usage!(
+            "Template dir must contain devcontainer.json: {}",
+            dir.display()
+        );
+    }
+    Ok(())
+}
+
+/// Copy a local template directory into `<worktree>/.devcontainer`.
+///
+/// Files already present with identical content are left alone; differing files are
+/// never overwritten. Permission bits are copied too, so scripts stay executable.
+/// Returns the paths written, relative to the worktree.
+pub(crate) fn copy_template_dir(template_dir: &Path, worktree_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_files(template_dir, Path::new(""), &mut files)?;
+    files.sort();
+
+    let dest_root = Path::new(".devcontainer");
+    let conflicts: Vec<String> = files
+        .iter()
+        .filter_map(|rel| {
+            let dest = worktree_dir.join(dest_root).join(rel);
+            if !dest.exists() {
+                return None;
+            }
+            let same = std::fs::read(&dest).ok() == std::fs::read(template_dir.join(rel)).ok();
+            (!same).then(|| dest_root.join(rel).display().to_string())
+        })
+        .collect();
+    if !conflicts.is_empty() {
+        bail_git_state!(
+            "Refusing to overwrite existing files in the worktree: {}",
+            conflicts.join(", ")
+        );
+    }
+
+    let mut written = Vec::new();
+    for rel in files {
+        let src = template_dir.join(&rel);
+        let dest = worktree_dir.join(dest_root).join(&rel);
+        if dest.exists() {
+            continue;
+        }
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::copy(&src, &dest)
+            .with_context(|| format!("Failed to copy {} to {}", src.display(), dest.display()))?;
+        written.push(dest_root.join(rel));
+    }
+    Ok(written)
+}
+
+fn collect_files(root: &Path, rel: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    let dir = root.join(rel);
+    for entry in
+        std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let entry = entry.with_context(|| format!("Failed to read {}", dir.display()))?;
+        let rel = rel.join(entry.file_name());
+        let file_type = entry
+            .file_type()
+            .with_context(|| format!("Failed to stat {}", entry.path().display()))?;
+        if file_type.is_dir() {
+            collect_files(root, &rel, out)?;
+        } else {
+            out.push(rel);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// Typed failures that callers (and wrapper scripts, via the exit code) can match on
+/// instead of grepping stderr. Display output is the human-facing message.
+#[derive(Debug)]
+pub(crate) enum PcError {
+    /// Invalid arguments or missing input (no TTY to prompt, bad names, ...).
+    Usage(String),
+    /// The repository/worktree state does not allow the operation.
+    GitState(String),
+    /// A required external tool is not installed.
+    ToolMissing { bin: String },
+    /// `git check-ref-format --branch` rejected the name.
+    BranchInvalid { name: String },
+    /// The target worktree location is taken by another branch.
+    WorktreeExists {
+        what: &'static str,
+        path: PathBuf,
+        existing_branch: String,
+    },
+    /// The operation would discard local changes; re-run with `--force`.
+    ForceRequired(String),
+    /// An external command exited unsuccessfully.
+    ExternalCommandFailed {
+        cmd: String,
+        status: String,
+        stderr_tail: String,
+    },
+}
+
+impl PcError {
+    pub(crate) fn exit_code(&self) -> u8 {
+        match self {
+            PcError::Usage(_) => 2,
+            PcError::GitState(_) => 3,
+            PcError::ToolMissing { .. } => 4,
+            PcError::BranchInvalid { .. } => 5,
+            PcError::WorktreeExists { .. } => 6,
+            PcError::ForceRequired(_) => 7,
+            PcError::ExternalCommandFailed { .. } => 8,
+        }
+    }
+}
+
+/// Exit code for any other failure.
+pub(crate) const EXIT_FAILURE: u8 = 1;
+
+/// Exit code table shown in `pc --help`.
+pub(crate) const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0  success
+  1  other failure
+  2  usage error (invalid arguments, no TTY for a required prompt)
+  3  git state (not a repository, missing ref, agent not found, ...)
+  4  required tool missing from PATH
+  5  invalid branch name
+  6  worktree location already used by a different branch
+  7  local changes would be lost (re-run with --force)
+  8  external command failed
+130  interrupted (Ctrl-C); a half-created agent is rolled back first";
+
+impl fmt::Display for PcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PcError::Usage(message) | PcError::GitState(message) => f.write_str(message),
+            PcError::ToolMissing { bin } => write!(f, "{bin} not found in PATH"),
+            PcError::BranchInvalid { name } => write!(f, "Invalid branch name: {name}"),
+            PcError::WorktreeExists {
+                what,
+                path,
+                existing_branch,
+            } => write!(
+                f,
+                "{what} already exists for a different branch: {} (existing: {existing_branch})",
+                path.display()
+            ),
+            PcError::ForceRequired(message) => f.write_str(message),
+            PcError::ExternalCommandFailed {
+                cmd,
+                status,
+                stderr_tail,
+            } => {
+                if stderr_tail.is_empty() {
+                    write!(f, "{cmd} failed with status: {status}")
+                } else {
+                    write!(f, "{cmd} failed: {stderr_tail}")
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for PcError {}
+
+/// Like `anyhow::bail!`, failing with [`PcError::Usage`].
+macro_rules! bail_usage {
+    ($($arg:tt)*) => {
+        return Err($crate::error::PcError::Usage(format!($($arg)*)).into())
+    };
+}
+
+/// Like `anyhow::bail!`, failing with [`PcError::GitState`].
+macro_rules! bail_git_state {
+    ($($arg:tt)*) => {
+        return Err($crate::error::PcError::GitState(format!($($arg)*)).into())
+    };
+}
+
+pub(crate) use bail_git_state;
+pub(crate) use bail_usage;
+
+pub(crate) trait ResultExt<T> {
+    /// Turn an error that carries no context chain into [`PcError::Usage`]
+    /// (its message is kept as-is).
+    fn or_usage(self) -> anyhow::Result<T>;
+}
+
+impl<T> ResultExt<T> for anyhow::Result<T> {
+    fn or_usage(self) -> anyhow::Result<T> {
+        self.map_err(|e| PcError::Usage(format!("{e:#}")).into())
+    }
+}
+
+pub(crate) fn exit_code(err: &anyhow::Error) -> u8 {
+    err.chain()
+        .find_map(|e| e.downcast_ref::<PcError>())
+        .map(PcError::exit_code)
+        .unwrap_or(EXIT_FAILURE)
+}
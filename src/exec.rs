@@ -1,12 +1,17 @@
 use std::process::{Command, ExitStatus};
 
-use anyhow::{bail, Context, Result};
+use anyhow::{Context, Result};
+
+use crate::error::PcError;
 
 pub(crate) fn ensure_in_path(bin: &str) -> Result<()> {
     if is_in_path(bin) {
         Ok(())
     } else {
-        bail!("{bin} not found in PATH");
+        Err(PcError::ToolMissing {
+            bin: bin.to_string(),
+        }
+        .into())
     }
 }
 
@@ -25,7 +30,17 @@ pub(crate) fn run_ok(mut cmd: Command) -> Result<ExitStatus> {
     if status.success() {
         Ok(status)
     } else {
-        bail!("Command failed with status: {status}");
+        let mut line = cmd.get_program().to_string_lossy().into_owned();
+        for arg in cmd.get_args() {
+            line.push(' ');
+            line.push_str(&arg.to_string_lossy());
+        }
+        Err(PcError::ExternalCommandFailed {
+            cmd: line,
+            status: status.to_string(),
+            stderr_tail: String::new(),
+        }
+        .into())
     }
 }
 
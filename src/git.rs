@@ -1,10 +1,14 @@
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use anyhow::{bail, Context, Result};
 use dialoguer::{theme::ColorfulTheme, Confirm};
 
+use crate::error::{bail_git_state, bail_usage, PcError};
 use crate::exec;
+use crate::i18n::{tr, trf, Msg};
+use crate::output;
 
 pub(crate) fn repo_root() -> Result<PathBuf> {
     let output = Command::new("git")
@@ -12,7 +16,7 @@ pub(crate) fn repo_root() -> Result<PathBuf> {
         .output()
         .context("Failed to run git rev-parse")?;
     if !output.status.success() {
-        bail!("Not a git repository (git rev-parse --show-toplevel failed)");
+        bail_git_state!("Not a git repository (git rev-parse --show-toplevel failed)");
     }
     let s = String::from_utf8(output.stdout).context("git output not utf8")?;
     let p = s.trim();
@@ -22,8 +26,9 @@ pub(crate) fn repo_root() -> Result<PathBuf> {
     Ok(PathBuf::from(p))
 }
 
-pub(crate) fn has_commit() -> Result<bool> {
+pub(crate) fn has_commit(repo_root: &Path) -> Result<bool> {
     let status = Command::new("git")
+        .current_dir(repo_root)
         .args(["rev-parse", "--verify", "--quiet", "HEAD"])
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
@@ -32,8 +37,9 @@ pub(crate) fn has_commit() -> Result<bool> {
     Ok(status.success())
 }
 
-pub(crate) fn ensure_ref_exists(name: &str) -> Result<()> {
+pub(crate) fn ensure_ref_exists(repo_root: &Path, name: &str) -> Result<()> {
     let status = Command::new("git")
+        .current_dir(repo_root)
         .args(["rev-parse", "--verify", "--quiet", name])
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
@@ -42,12 +48,13 @@ pub(crate) fn ensure_ref_exists(name: &str) -> Result<()> {
     if status.success() {
         Ok(())
     } else {
-        bail!("Base ref not found: {name}");
+        bail_git_state!("Base ref not found: {name}");
     }
 }
 
-pub(crate) fn ensure_branch_name_valid(name: &str) -> Result<()> {
+pub(crate) fn ensure_branch_name_valid(repo_root: &Path, name: &str) -> Result<()> {
     let status = Command::new("git")
+        .current_dir(repo_root)
         .args(["check-ref-format", "--branch", name])
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
@@ -56,49 +63,129 @@ pub(crate) fn ensure_branch_name_valid(name: &str) -> Result<()> {
     if status.success() {
         Ok(())
     } else {
-        bail!("Invalid branch name: {name}");
+        Err(PcError::BranchInvalid {
+            name: name.to_string(),
+        }
+        .into())
     }
 }
 
-pub(crate) fn branch_exists_local(branch_name: &str) -> Result<bool> {
+pub(crate) fn branch_exists_local(repo_root: &Path, branch_name: &str) -> Result<bool> {
     let ref_name = format!("refs/heads/{branch_name}");
     Ok(Command::new("git")
+        .current_dir(repo_root)
         .args(["show-ref", "--verify", "--quiet", &ref_name])
         .status()
         .map(|s| s.success())
         .unwrap_or(false))
 }
 
-pub(crate) fn worktree_add(worktree_dir: &Path, branch_name: &str, base_ref: &str) -> Result<bool> {
-    let branch_exists = branch_exists_local(branch_name)?;
-
+/// Creates `branch_name` from `base_ref` unless `branch_exists`.
+/// When the branch is created, `track` sets its upstream to `base_ref`; otherwise no
+/// upstream is set (regardless of `branch.autoSetupMerge`).
+/// A non-empty `sparse` checks out only those directories (cone mode) instead of the
+/// whole tree; if that fails, the worktree (and the branch, if created) are removed again.
+pub(crate) fn worktree_add(
+    repo_root: &Path,
+    worktree_dir: &Path,
+    branch_name: &str,
+    base_ref: &str,
+    branch_exists: bool,
+    track: bool,
+    sparse: &[String],
+) -> Result<()> {
     let mut cmd = Command::new("git");
+    cmd.current_dir(repo_root).args(["worktree", "add"]);
+    if output::is_quiet() {
+        cmd.arg("--quiet");
+    }
+    if !sparse.is_empty() {
+        cmd.arg("--no-checkout");
+    }
+    if !branch_exists {
+        cmd.arg(if track { "--track" } else { "--no-track" });
+    }
     if branch_exists {
-        cmd.args(["worktree", "add"])
-            .arg(worktree_dir)
-            .arg(branch_name);
+        cmd.arg(worktree_dir).arg(branch_name);
     } else {
-        cmd.args(["worktree", "add", "-b"])
+        cmd.arg("-b")
             .arg(branch_name)
             .arg(worktree_dir)
             .arg(base_ref);
     }
     exec::run_ok(cmd).context("git worktree add failed")?;
-    Ok(!branch_exists)
+    if !sparse.is_empty() {
+        if let Err(e) = sparse_checkout(worktree_dir, sparse) {
+            // Callers see only the error, so take back what was just added.
+            let _ = worktree_remove(repo_root, worktree_dir, true);
+            if !branch_exists {
+                let _ = Command::new("git")
+                    .current_dir(repo_root)
+                    .args(["branch", "-D", branch_name])
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null())
+                    .status();
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Restrict a `--no-checkout` worktree to the `paths` cone, then populate it.
+/// The sparse settings are per-worktree; the main checkout is not affected.
+fn sparse_checkout(worktree_dir: &Path, paths: &[String]) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(worktree_dir)
+        .args(["sparse-checkout", "set", "--cone", "--"])
+        .args(paths);
+    exec::run_ok(cmd).context("git sparse-checkout set failed")?;
+
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(worktree_dir)
+        .args(["read-tree", "-mu", "HEAD"]);
+    exec::run_ok(cmd).context("git checkout of the sparse cone failed")?;
+    Ok(())
 }
 
-pub(crate) fn worktree_remove(path: &Path, force: bool) -> Result<bool> {
+/// `git worktree lock`, with `--reason` when `reason` is non-empty.
+pub(crate) fn worktree_lock(repo_root: &Path, path: &Path, reason: &str) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(repo_root).args(["worktree", "lock"]);
+    if !reason.is_empty() {
+        cmd.args(["--reason", reason]);
+    }
+    cmd.arg(path);
+    exec::run_ok(cmd).context("git worktree lock failed")?;
+    Ok(())
+}
+
+pub(crate) fn worktree_unlock(repo_root: &Path, path: &Path) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(repo_root)
+        .args(["worktree", "unlock"])
+        .arg(path);
+    exec::run_ok(cmd).context("git worktree unlock failed")?;
+    Ok(())
+}
+
+pub(crate) fn worktree_remove(repo_root: &Path, path: &Path, force: bool) -> Result<bool> {
     if force {
         let mut cmd = Command::new("git");
-        cmd.args(["worktree", "remove", "--force"]).arg(path);
+        cmd.current_dir(repo_root)
+            .args(["worktree", "remove", "--force"])
+            .arg(path);
         exec::run_ok(cmd).context("git worktree remove failed")?;
         return Ok(true);
     }
-    worktree_remove_interactive(path)
+    worktree_remove_interactive(repo_root, path)
 }
 
-fn worktree_remove_interactive(path: &Path) -> Result<bool> {
+fn worktree_remove_interactive(repo_root: &Path, path: &Path) -> Result<bool> {
     let output = Command::new("git")
+        .current_dir(repo_root)
         .args(["worktree", "remove"])
         .arg(path)
         .output()
@@ -115,15 +202,12 @@ fn worktree_remove_interactive(path: &Path) -> Result<bool> {
         println!("{stderr_trimmed}");
         if let Ok(p) = status_porcelain(path) {
             if !p.trim().is_empty() {
-                println!("Worktree has local changes/untracked files:");
+                println!("{}", tr(Msg::WorktreeHasLocalChanges));
                 println!("{p}");
             }
         }
         let ok = Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt(format!(
-                "git worktree remove failed ({}). Retry with --force?",
-                path.display()
-            ))
+            .with_prompt(trf(Msg::ConfirmRetryRemoveWithForce, &[&path.display()]))
             .default(false)
             .interact()
             .context("Prompt failed")?;
@@ -131,6 +215,7 @@ fn worktree_remove_interactive(path: &Path) -> Result<bool> {
             return Ok(false);
         }
         let status = Command::new("git")
+            .current_dir(repo_root)
             .args(["worktree", "remove", "--force"])
             .arg(path)
             .status()
@@ -138,16 +223,84 @@ fn worktree_remove_interactive(path: &Path) -> Result<bool> {
         if status.success() {
             return Ok(true);
         }
-        bail!("git worktree remove --force failed with status: {status}");
+        return Err(PcError::ExternalCommandFailed {
+            cmd: "git worktree remove --force".to_string(),
+            status: status.to_string(),
+            stderr_tail: String::new(),
+        }
+        .into());
+    }
+
+    if suggests_force {
+        return Err(PcError::ForceRequired(format!(
+            "git worktree remove failed: {stderr_trimmed}"
+        ))
+        .into());
     }
+    Err(PcError::ExternalCommandFailed {
+        cmd: "git worktree remove".to_string(),
+        status: output.status.to_string(),
+        stderr_tail: stderr_trimmed.to_string(),
+    }
+    .into())
+}
 
-    if stderr_trimmed.is_empty() {
-        bail!("git worktree remove failed with status: {}", output.status);
+/// `git worktree add` of an existing branch, run in another repository (`repo`).
+pub(crate) fn worktree_add_in(repo: &Path, worktree_dir: &Path, branch_name: &str) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(repo).args(["worktree", "add"]);
+    if output::is_quiet() {
+        cmd.arg("--quiet");
     }
-    bail!("git worktree remove failed: {stderr_trimmed}");
+    cmd.arg(worktree_dir).arg(branch_name);
+    exec::run_ok(cmd).context("git worktree add failed")?;
+    Ok(())
+}
+
+/// Non-interactive `git worktree remove` of a worktree of another repository (`repo`).
+pub(crate) fn worktree_remove_in(repo: &Path, worktree_dir: &Path, force: bool) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(repo).args(["worktree", "remove"]);
+    if force {
+        cmd.arg("--force");
+    }
+    let output = cmd
+        .arg(worktree_dir)
+        .output()
+        .context("Failed to run git worktree remove")?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stderr = stderr.trim();
+    if stderr.contains("use --force") {
+        return Err(PcError::ForceRequired(format!(
+            "git worktree remove failed for {}: {stderr}",
+            worktree_dir.display()
+        ))
+        .into());
+    }
+    Err(PcError::ExternalCommandFailed {
+        cmd: format!("git -C {} worktree remove", repo.display()),
+        status: output.status.to_string(),
+        stderr_tail: stderr.to_string(),
+    }
+    .into())
+}
+
+/// Whether `repo` (any repository, not the current one) has local branch `branch_name`.
+pub(crate) fn branch_exists_in(repo: &Path, branch_name: &str) -> bool {
+    Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["show-ref", "--verify", "--quiet"])
+        .arg(format!("refs/heads/{branch_name}"))
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
 }
 
-fn status_porcelain(worktree_dir: &Path) -> Result<String> {
+pub(crate) fn status_porcelain(worktree_dir: &Path) -> Result<String> {
     let output = Command::new("git")
         .current_dir(worktree_dir)
         .args(["status", "--porcelain=v1", "--untracked-files=all"])
@@ -159,84 +312,252 @@ fn status_porcelain(worktree_dir: &Path) -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
-pub(crate) fn branch_delete_force(repo_root: &Path, branch_name: &str) -> Result<()> {
-    let ref_name = format!("refs/heads/{branch_name}");
-    let exists = Command::new("git")
-        .current_dir(repo_root)
-        .args(["show-ref", "--verify", "--quiet", &ref_name])
-        .status()
-        .context("Failed to run git show-ref --verify")?;
-    if !exists.success() {
-        return Ok(());
+/// Paths with uncommitted changes (tracked or untracked) in `worktree_dir`, relative to it.
+/// Both sides of a rename are included.
+pub(crate) fn dirty_paths(worktree_dir: &Path) -> Result<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .current_dir(worktree_dir)
+        .args(["status", "--porcelain=v1", "-z", "--untracked-files=all"])
+        .output()
+        .context("Failed to run git status")?;
+    if !output.status.success() {
+        bail!("git status failed");
+    }
+    let text = String::from_utf8(output.stdout).context("git output not utf8")?;
+    let mut out = Vec::new();
+    let mut entries = text.split('\0').filter(|e| !e.is_empty());
+    while let Some(entry) = entries.next() {
+        let (code, path) = entry.split_at(entry.len().min(3));
+        out.push(PathBuf::from(path));
+        // Renames/copies are followed by the original path as a separate entry.
+        if code.starts_with('R') || code.starts_with('C') {
+            if let Some(orig) = entries.next() {
+                out.push(PathBuf::from(orig));
+            }
+        }
     }
+    Ok(out)
+}
 
-    let status = Command::new("git")
-        .current_dir(repo_root)
-        .args(["branch", "-D", branch_name])
-        .status()
-        .context("Failed to run git branch -D")?;
-    if status.success() {
-        Ok(())
-    } else {
-        bail!("git branch -D {branch_name} failed with status: {status}");
+/// Untracked files in `worktree_dir` (relative to it) that only count as untracked because
+/// none of the gitignore `patterns` is in effect. Nothing is written to any exclude file.
+pub(crate) fn untracked_matching(
+    worktree_dir: &Path,
+    patterns: &[String],
+) -> Result<HashSet<PathBuf>> {
+    if patterns.is_empty() {
+        return Ok(HashSet::new());
     }
+    let untracked = |patterns: &[String]| -> Result<HashSet<PathBuf>> {
+        let mut cmd = Command::new("git");
+        cmd.current_dir(worktree_dir)
+            .args(["ls-files", "-z", "--others", "--exclude-standard"]);
+        for p in patterns {
+            cmd.arg(format!("--exclude={p}"));
+        }
+        let output = cmd.output().context("Failed to run git ls-files")?;
+        if !output.status.success() {
+            bail!("git ls-files failed");
+        }
+        let text = String::from_utf8(output.stdout).context("git output not utf8")?;
+        Ok(text
+            .split('\0')
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+            .collect())
+    };
+    let remaining = untracked(patterns)?;
+    Ok(untracked(&[])?
+        .into_iter()
+        .filter(|p| !remaining.contains(p))
+        .collect())
 }
 
-pub(crate) fn worktree_path_for_branch(branch_name: &str) -> Result<Option<PathBuf>> {
+/// `git hash-object` ids of `paths` (relative to `worktree_dir`), in order. Content is
+/// hashed as-is, without clean filters.
+pub(crate) fn hash_objects(worktree_dir: &Path, paths: &[PathBuf]) -> Result<Vec<String>> {
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
     let output = Command::new("git")
-        .args(["worktree", "list", "--porcelain"])
+        .current_dir(worktree_dir)
+        .args(["hash-object", "--no-filters", "--"])
+        .args(paths)
         .output()
-        .context("Failed to run git worktree list")?;
+        .context("Failed to run git hash-object")?;
     if !output.status.success() {
-        bail!("git worktree list failed");
+        bail!("git hash-object failed");
     }
     let text = String::from_utf8(output.stdout).context("git output not utf8")?;
+    Ok(text.lines().map(str::to_string).collect())
+}
 
-    let wanted = format!("refs/heads/{branch_name}");
-    let mut current_path: Option<PathBuf> = None;
-    for line in text.lines() {
-        if let Some(rest) = line.strip_prefix("worktree ") {
-            current_path = Some(PathBuf::from(rest.trim()));
-            continue;
-        }
-        if let Some(rest) = line.strip_prefix("branch ") {
-            if rest.trim() == wanted {
-                return Ok(current_path.clone());
-            }
-        }
+pub(crate) fn rev_parse_commit(repo_root: &Path, name: &str) -> Result<String> {
+    let output = Command::new("git")
+        .current_dir(repo_root)
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("{name}^{{commit}}"))
+        .output()
+        .context("Failed to run git rev-parse --verify")?;
+    if !output.status.success() {
+        bail_git_state!("Base ref not found: {name}");
     }
-    Ok(None)
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
-pub(crate) fn worktree_path_for_basename(name: &str) -> Result<Option<PathBuf>> {
+/// What a `--base` value names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BaseKind {
+    Branch,
+    RemoteBranch,
+    Tag,
+    /// A commit id or any other revision expression (`HEAD~2`, ...).
+    Commit,
+}
+
+impl BaseKind {
+    pub(crate) fn is_branch(self) -> bool {
+        matches!(self, BaseKind::Branch | BaseKind::RemoteBranch)
+    }
+}
+
+/// Classify an existing `base_ref`. A short name that is both a local branch and a tag
+/// is a usage error: git would silently pick the tag.
+pub(crate) fn base_kind(repo_root: &Path, base_ref: &str) -> Result<BaseKind> {
+    if base_ref.starts_with("refs/heads/") {
+        return Ok(BaseKind::Branch);
+    }
+    if base_ref.starts_with("refs/remotes/") {
+        return Ok(BaseKind::RemoteBranch);
+    }
+    if base_ref.starts_with("refs/tags/") {
+        return Ok(BaseKind::Tag);
+    }
+    let head = format!("refs/heads/{base_ref}");
+    let tag = format!("refs/tags/{base_ref}");
+    let remote = format!("refs/remotes/{base_ref}");
+    let refs = existing_refs(repo_root, &[head.clone(), tag.clone(), remote.clone()])?;
+    match (refs.contains(&head), refs.contains(&tag)) {
+        (true, true) => bail_usage!(
+            "Base ref is ambiguous: {base_ref} is both a branch and a tag; use --base refs/heads/{base_ref} or --base refs/tags/{base_ref}"
+        ),
+        (true, false) => Ok(BaseKind::Branch),
+        (false, true) => Ok(BaseKind::Tag),
+        (false, false) if refs.contains(&remote) => Ok(BaseKind::RemoteBranch),
+        (false, false) => Ok(BaseKind::Commit),
+    }
+}
+
+/// Contents of `path` (relative to the repo root) at `rev`, or `None` if it is not there.
+pub(crate) fn show_file(repo_root: &Path, rev: &str, path: &str) -> Result<Option<Vec<u8>>> {
+    let spec = format!("{rev}:{path}");
+    let exists = Command::new("git")
+        .current_dir(repo_root)
+        .args(["cat-file", "-e", &spec])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .context("Failed to run git cat-file")?
+        .success();
+    if !exists {
+        return Ok(None);
+    }
     let output = Command::new("git")
-        .args(["worktree", "list", "--porcelain"])
+        .current_dir(repo_root)
+        .args(["cat-file", "blob", &spec])
         .output()
-        .context("Failed to run git worktree list")?;
+        .context("Failed to run git cat-file")?;
     if !output.status.success() {
-        bail!("git worktree list failed");
+        bail!("Failed to read {spec}");
     }
-    let text = String::from_utf8(output.stdout).context("git output not utf8")?;
+    Ok(Some(output.stdout))
+}
 
-    for line in text.lines() {
-        if let Some(rest) = line.strip_prefix("worktree ") {
-            let p = PathBuf::from(rest.trim());
-            if p.file_name().and_then(|s| s.to_str()) == Some(name) {
-                return Ok(Some(p));
+/// Commit the working tree state of `repo_root` (including untracked files) on top of
+/// HEAD without touching its index, branch or files. Returns the new commit id.
+pub(crate) fn commit_wip_snapshot(repo_root: &Path) -> Result<String> {
+    let output = Command::new("git")
+        .current_dir(repo_root)
+        .args(["rev-parse", "--git-path", "pc/wip-index"])
+        .output()
+        .context("Failed to run git rev-parse --git-path")?;
+    if !output.status.success() {
+        bail!("git rev-parse --git-path failed");
+    }
+    let index = repo_root.join(String::from_utf8_lossy(&output.stdout).trim());
+    if let Some(parent) = index.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    let git = |args: &[&str]| -> Result<String> {
+        let output = Command::new("git")
+            .current_dir(repo_root)
+            .env("GIT_INDEX_FILE", &index)
+            .args(args)
+            .output()
+            .with_context(|| format!("Failed to run git {}", args[0]))?;
+        if !output.status.success() {
+            return Err(PcError::ExternalCommandFailed {
+                cmd: format!("git {}", args.join(" ")),
+                status: output.status.to_string(),
+                stderr_tail: String::from_utf8_lossy(&output.stderr).trim().to_string(),
             }
+            .into());
         }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+
+    let result = git(&["read-tree", "HEAD"])
+        .and_then(|_| git(&["add", "-A"]))
+        .and_then(|_| git(&["write-tree"]))
+        .and_then(|tree| {
+            git(&[
+                "commit-tree",
+                &tree,
+                "-p",
+                "HEAD",
+                "-m",
+                "WIP: uncommitted changes from the main worktree",
+            ])
+        });
+    let _ = std::fs::remove_file(&index);
+    result
+}
+
+pub(crate) fn branch_delete_force(repo_root: &Path, branch_name: &str) -> Result<()> {
+    let ref_name = format!("refs/heads/{branch_name}");
+    let exists = Command::new("git")
+        .current_dir(repo_root)
+        .args(["show-ref", "--verify", "--quiet", &ref_name])
+        .status()
+        .context("Failed to run git show-ref --verify")?;
+    if !exists.success() {
+        return Ok(());
+    }
+
+    let status = Command::new("git")
+        .current_dir(repo_root)
+        .args(["branch", "-D", branch_name])
+        .status()
+        .context("Failed to run git branch -D")?;
+    if status.success() {
+        Ok(())
+    } else {
+        bail!("git branch -D {branch_name} failed with status: {status}");
     }
-    Ok(None)
 }
 
 #[derive(Debug, Clone)]
 pub(crate) struct WorktreeEntry {
     pub(crate) path: PathBuf,
     pub(crate) branch: Option<String>,
+    /// `Some(reason)` while `git worktree lock` is in effect (the reason may be empty).
+    pub(crate) locked: Option<String>,
 }
 
-pub(crate) fn worktrees() -> Result<Vec<WorktreeEntry>> {
+pub(crate) fn worktrees(repo_root: &Path) -> Result<Vec<WorktreeEntry>> {
     let output = Command::new("git")
+        .current_dir(repo_root)
         .args(["worktree", "list", "--porcelain"])
         .output()
         .context("Failed to run git worktree list")?;
@@ -256,6 +577,7 @@ pub(crate) fn worktrees() -> Result<Vec<WorktreeEntry>> {
             current = Some(WorktreeEntry {
                 path: PathBuf::from(rest.trim()),
                 branch: None,
+                locked: None,
             });
             continue;
         }
@@ -265,6 +587,12 @@ pub(crate) fn worktrees() -> Result<Vec<WorktreeEntry>> {
             }
             continue;
         }
+        if line == "locked" || line.starts_with("locked ") {
+            if let Some(e) = current.as_mut() {
+                e.locked = Some(line["locked".len()..].trim_start().to_string());
+            }
+            continue;
+        }
         if line.trim() == "detached" {
             if let Some(e) = current.as_mut() {
                 e.branch = None;
@@ -280,51 +608,171 @@ pub(crate) fn worktrees() -> Result<Vec<WorktreeEntry>> {
     Ok(out)
 }
 
-pub(crate) fn worktree_entry_for_path(path: &Path) -> Result<Option<WorktreeEntry>> {
+pub(crate) fn worktree_entry_for_path(
+    repo_root: &Path,
+    path: &Path,
+) -> Result<Option<WorktreeEntry>> {
+    Ok(find_worktree_for_path(&worktrees(repo_root)?, path).cloned())
+}
+
+// Lookups over a single `worktrees()` snapshot, so callers that need several of
+// them spawn `git worktree list` once.
+
+pub(crate) fn find_worktree_for_path<'a>(
+    list: &'a [WorktreeEntry],
+    path: &Path,
+) -> Option<&'a WorktreeEntry> {
     let wanted = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
-    for e in worktrees()? {
+    list.iter().find(|e| {
         let p = std::fs::canonicalize(&e.path).unwrap_or_else(|_| e.path.clone());
-        if p == wanted {
-            return Ok(Some(e));
-        }
+        p == wanted
+    })
+}
+
+pub(crate) fn find_worktree_for_branch<'a>(
+    list: &'a [WorktreeEntry],
+    branch_name: &str,
+) -> Option<&'a WorktreeEntry> {
+    let wanted = format!("refs/heads/{branch_name}");
+    list.iter()
+        .find(|e| e.branch.as_deref() == Some(wanted.as_str()))
+}
+
+pub(crate) fn find_worktree_for_basename<'a>(
+    list: &'a [WorktreeEntry],
+    name: &str,
+) -> Option<&'a WorktreeEntry> {
+    list.iter()
+        .find(|e| e.path.file_name().and_then(|s| s.to_str()) == Some(name))
+}
+
+/// The subset of `refs` (full names, e.g. `refs/heads/main`) that exist, using a
+/// single `git for-each-ref`.
+pub(crate) fn existing_refs(repo_root: &Path, refs: &[String]) -> Result<HashSet<String>> {
+    let output = Command::new("git")
+        .current_dir(repo_root)
+        .args(["for-each-ref", "--format=%(refname)"])
+        .args(refs)
+        .output()
+        .context("Failed to run git for-each-ref")?;
+    if !output.status.success() {
+        bail!("git for-each-ref failed");
     }
-    Ok(None)
+    let text = String::from_utf8(output.stdout).context("git output not utf8")?;
+    Ok(text
+        .lines()
+        .filter(|l| refs.iter().any(|r| r == l))
+        .map(str::to_string)
+        .collect())
 }
 
 pub(crate) struct BranchInfo {
     pub(crate) name: String,
     pub(crate) committer_date: String,
+    /// Remote-tracking branch (`refs/remotes/...`), e.g. `origin/main`.
+    pub(crate) remote: bool,
 }
 
-pub(crate) fn local_branches_by_recent() -> Result<Vec<BranchInfo>> {
-    let output = Command::new("git")
-        .args([
-            "for-each-ref",
-            "--sort=-committerdate",
-            "--format=%(refname:short)\t%(committerdate:iso8601)",
-            "refs/heads/",
-        ])
-        .output()
-        .context("Failed to run git for-each-ref")?;
+/// Local branches (and remote-tracking branches if `include_remotes`), newest first.
+/// Symbolic refs such as `origin/HEAD` are skipped.
+pub(crate) fn branches_by_recent(
+    repo_root: &Path,
+    include_remotes: bool,
+) -> Result<Vec<BranchInfo>> {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(repo_root).args([
+        "for-each-ref",
+        "--sort=-committerdate",
+        "--format=%(refname)\t%(refname:short)\t%(symref)\t%(committerdate:iso8601)",
+        "refs/heads/",
+    ]);
+    if include_remotes {
+        cmd.arg("refs/remotes/");
+    }
+    let output = cmd.output().context("Failed to run git for-each-ref")?;
     if !output.status.success() {
         bail!("git for-each-ref failed");
     }
     let text = String::from_utf8(output.stdout).context("git output not utf8")?;
     let mut out = Vec::new();
     for line in text.lines() {
-        let line = line.trim();
-        if line.is_empty() {
+        let mut fields = line.splitn(4, '\t');
+        let (Some(full), Some(name), Some(symref)) = (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        if name.is_empty() || !symref.is_empty() {
             continue;
         }
-        let (name, date) = line.split_once('\t').unwrap_or((line, ""));
         out.push(BranchInfo {
             name: name.to_string(),
-            committer_date: date.to_string(),
+            committer_date: fields.next().unwrap_or("").to_string(),
+            remote: full.starts_with("refs/remotes/"),
         });
     }
     Ok(out)
 }
 
+/// Upstream of a local branch as `<remote>/<branch>`, if one is configured.
+pub(crate) fn upstream_of(repo_root: &Path, branch_name: &str) -> Result<Option<String>> {
+    let output = Command::new("git")
+        .current_dir(repo_root)
+        .args(["rev-parse", "--abbrev-ref", "--symbolic-full-name"])
+        .arg(format!("{branch_name}@{{upstream}}"))
+        .stderr(std::process::Stdio::null())
+        .output()
+        .context("Failed to run git rev-parse @{upstream}")?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let s = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((!s.is_empty()).then_some(s))
+}
+
+/// `git push -u <remote> <branch>` from `worktree_dir`.
+pub(crate) fn push_set_upstream(
+    worktree_dir: &Path,
+    remote: &str,
+    branch_name: &str,
+) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(worktree_dir).args(["push", "-u"]);
+    if output::is_quiet() {
+        cmd.arg("--quiet");
+    }
+    cmd.arg(remote).arg(branch_name);
+    exec::run_ok(cmd)?;
+    Ok(())
+}
+
+pub(crate) fn remotes(repo_root: &Path) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .current_dir(repo_root)
+        .arg("remote")
+        .output()
+        .context("Failed to run git remote")?;
+    if !output.status.success() {
+        bail!("git remote failed");
+    }
+    let text = String::from_utf8(output.stdout).context("git output not utf8")?;
+    Ok(text.lines().map(|l| l.trim().to_string()).collect())
+}
+
+/// `git fetch --prune` from `remote`, or from all remotes.
+pub(crate) fn fetch(repo_root: &Path, remote: Option<&str>) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(repo_root).args(["fetch", "--prune"]);
+    if output::is_quiet() {
+        cmd.arg("--quiet");
+    }
+    match remote {
+        Some(r) => cmd.arg(r),
+        None => cmd.arg("--all"),
+    };
+    exec::run_ok(cmd).context("git fetch failed")?;
+    Ok(())
+}
+
 pub(crate) fn ensure_exclude(worktree_dir: &Path, pattern: &str) -> Result<()> {
     let output = Command::new("git")
         .current_dir(worktree_dir)
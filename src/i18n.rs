@@ -0,0 +1,188 @@
+//! Message catalog for interactive prompts and status lines.
+//!
+//! Strings are looked up by [`Msg`] key; a language that lacks a translation falls
+//! back to English. Errors and warnings are not translated (scripts and tests match
+//! on them). The language comes from `PC_LANG`, then `LANG`.
+
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Lang {
+    En,
+    ZhCn,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Msg {
+    Cancelled,
+    CancelledBranchNotCreated,
+    CancelledWorktreeNotRemoved,
+    ConfirmCreateBranch,
+    ConfirmRemoveWorktree,
+    ConfirmRemoveWorktreeOnBranch,
+    ConfirmRetryRemoveWithForce,
+    ConfirmForceRemove,
+    TypeToConfirm,
+    PromptNewBranchName,
+    BranchNameEmpty,
+    SelectAgentToRemove,
+    SelectWorktreeToRemove,
+    SelectBaseBranch,
+    ShowRemoteBranches,
+    HideRemoteBranches,
+    SelectBranchToOpen,
+    WorktreeHasLocalChanges,
+    SummaryAgent,
+    SummaryWorktree,
+    SummaryBranch,
+    SummaryDevcontainer,
+    RemovedWorktreeForBranch,
+    RemovedWorktree,
+    WipPrompt,
+    WipContinue,
+    WipCommit,
+    WipCopy,
+}
+
+fn en(msg: Msg) -> &'static str {
+    match msg {
+        Msg::Cancelled => "Cancelled.",
+        Msg::CancelledBranchNotCreated => "Cancelled. Branch not created: {0}",
+        Msg::CancelledWorktreeNotRemoved => "Cancelled. Worktree not removed: {0}",
+        Msg::ConfirmCreateBranch => "Create new branch {0} from {1}?",
+        Msg::ConfirmRemoveWorktree => "Remove worktree: {0}",
+        Msg::ConfirmRemoveWorktreeOnBranch => "Remove worktree: {0} (branch: {1})",
+        Msg::ConfirmRetryRemoveWithForce => "git worktree remove failed ({0}). Retry with --force?",
+        Msg::ConfirmForceRemove => "Remove {0} anyway, discarding these changes (--force)?",
+        Msg::TypeToConfirm => "Type '{0}' to confirm",
+        Msg::PromptNewBranchName => "New branch name (base: {0})",
+        Msg::BranchNameEmpty => "Branch name cannot be empty",
+        Msg::SelectAgentToRemove => "Select agent to remove",
+        Msg::SelectWorktreeToRemove => "Select worktree to remove",
+        Msg::SelectBaseBranch => "Select base branch",
+        Msg::ShowRemoteBranches => "[show remote branches]",
+        Msg::HideRemoteBranches => "[hide remote branches]",
+        Msg::SelectBranchToOpen => "Select branch to open as worktree",
+        Msg::WorktreeHasLocalChanges => "Worktree has local changes/untracked files:",
+        Msg::SummaryAgent => "Agent:    {0}",
+        Msg::SummaryWorktree => "Worktree: {0}",
+        Msg::SummaryBranch => "Branch:   {0}",
+        Msg::SummaryDevcontainer => "Devcontainer: {0} file(s) from {1}",
+        Msg::RemovedWorktreeForBranch => "Removed worktree for {0}",
+        Msg::RemovedWorktree => "Removed worktree {0}",
+        Msg::WipPrompt => {
+            "Current worktree has {0} uncommitted change(s); the new worktree starts from {1} without them"
+        }
+        Msg::WipContinue => "Continue without them",
+        Msg::WipCommit => "Snapshot them into a WIP commit and branch from it",
+        Msg::WipCopy => "Copy the changed files into the new worktree",
+    }
+}
+
+fn zh_cn(msg: Msg) -> Option<&'static str> {
+    Some(match msg {
+        Msg::Cancelled => "已取消。",
+        Msg::CancelledBranchNotCreated => "已取消，未创建分支：{0}",
+        Msg::CancelledWorktreeNotRemoved => "已取消，未删除 worktree：{0}",
+        Msg::ConfirmCreateBranch => "从 {1} 创建新分支 {0}？",
+        Msg::ConfirmRemoveWorktree => "删除 worktree：{0}",
+        Msg::ConfirmRemoveWorktreeOnBranch => "删除 worktree：{0}（分支：{1}）",
+        Msg::ConfirmRetryRemoveWithForce => {
+            "git worktree remove 失败（{0}），是否用 --force 重试？"
+        }
+        Msg::ConfirmForceRemove => "仍然删除 {0} 并丢弃这些修改（--force）？",
+        Msg::TypeToConfirm => "输入 '{0}' 以确认",
+        Msg::PromptNewBranchName => "新分支名（基于：{0}）",
+        Msg::BranchNameEmpty => "分支名不能为空",
+        Msg::SelectAgentToRemove => "选择要删除的 agent",
+        Msg::SelectWorktreeToRemove => "选择要删除的 worktree",
+        Msg::SelectBaseBranch => "选择基分支",
+        Msg::ShowRemoteBranches => "[显示远程分支]",
+        Msg::HideRemoteBranches => "[隐藏远程分支]",
+        Msg::SelectBranchToOpen => "选择要打开为 worktree 的分支",
+        Msg::WorktreeHasLocalChanges => "worktree 中有未提交的修改或未追踪文件：",
+        Msg::SummaryAgent => "Agent：   {0}",
+        Msg::SummaryWorktree => "工作树：  {0}",
+        Msg::SummaryBranch => "分支：    {0}",
+        Msg::SummaryDevcontainer => "Devcontainer：从 {1} 复制了 {0} 个文件",
+        Msg::RemovedWorktreeForBranch => "已删除 {0} 的 worktree",
+        Msg::RemovedWorktree => "已删除 worktree {0}",
+        Msg::WipPrompt => {
+            "当前 worktree 有 {0} 处未提交的修改，新 worktree 基于 {1} 创建，不会包含这些修改"
+        }
+        Msg::WipContinue => "忽略这些修改，继续",
+        Msg::WipCommit => "将修改快照为 WIP commit，并从它创建新分支",
+        Msg::WipCopy => "把修改过的文件复制到新 worktree",
+    })
+}
+
+fn parse_lang(value: &str) -> Option<Lang> {
+    let value = value.trim().to_ascii_lowercase();
+    if value.is_empty() {
+        return None;
+    }
+    if value.starts_with("zh") {
+        Some(Lang::ZhCn)
+    } else {
+        Some(Lang::En)
+    }
+}
+
+fn lang() -> Lang {
+    static LANG: OnceLock<Lang> = OnceLock::new();
+    *LANG.get_or_init(|| {
+        ["PC_LANG", "LANG"]
+            .iter()
+            .filter_map(|k| std::env::var(k).ok())
+            .find_map(|v| parse_lang(&v))
+            .unwrap_or(Lang::En)
+    })
+}
+
+fn lookup(lang: Lang, msg: Msg) -> &'static str {
+    match lang {
+        Lang::En => en(msg),
+        Lang::ZhCn => zh_cn(msg).unwrap_or_else(|| en(msg)),
+    }
+}
+
+fn format_with(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = template.to_string();
+    for (i, arg) in args.iter().enumerate() {
+        out = out.replace(&format!("{{{i}}}"), &arg.to_string());
+    }
+    out
+}
+
+/// Message without placeholders.
+pub(crate) fn tr(msg: Msg) -> &'static str {
+    lookup(lang(), msg)
+}
+
+/// Message with `{0}`, `{1}`, ... replaced by `args`.
+pub(crate) fn trf(msg: Msg, args: &[&dyn Display]) -> String {
+    format_with(tr(msg), args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lang_prefers_chinese_for_zh_locales() {
+        assert_eq!(parse_lang("zh_CN.UTF-8"), Some(Lang::ZhCn));
+        assert_eq!(parse_lang("zh-CN"), Some(Lang::ZhCn));
+        assert_eq!(parse_lang("C.UTF-8"), Some(Lang::En));
+        assert_eq!(parse_lang(""), None);
+    }
+
+    #[test]
+    fn placeholders_are_positional() {
+        let s = format_with(
+            lookup(Lang::ZhCn, Msg::ConfirmCreateBranch),
+            &[&"feat/a", &"main"],
+        );
+        assert_eq!(s, "从 main 创建新分支 feat/a？");
+    }
+}
@@ -0,0 +1,106 @@
+//! Rollback for the part of `pc new` that leaves state behind.
+//!
+//! A [`Guard`] collects undo steps as things are created. If it is dropped without
+//! [`Guard::commit`] (an error or `?` anywhere in between), the steps run newest first.
+//! Ctrl-C, SIGTERM or SIGHUP while a guard is alive run the same steps and then exit
+//! with 130, so an interrupted `pc new` does not leave a half-created agent either.
+//! Once no guard is alive the handler steps aside: the signal gets its default action,
+//! as if pc had never installed one.
+
+use std::sync::{Mutex, Once};
+
+use anyhow::{Context, Result};
+
+type Step = Box<dyn FnOnce() + Send>;
+
+/// Undo steps of the live guard (`None` when there is none).
+static STEPS: Mutex<Option<Vec<Step>>> = Mutex::new(None);
+static INSTALL: Once = Once::new();
+
+/// Exit status for a process killed by SIGINT, as shells report it.
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// Start collecting undo steps; everything registered is undone unless
+/// [`Guard::commit`] is called.
+pub(crate) fn guard() -> Result<Guard> {
+    let mut installed = Ok(());
+    INSTALL.call_once(|| installed = install());
+    installed.context("Failed to install Ctrl-C handler")?;
+    *lock() = Some(Vec::new());
+    Ok(Guard)
+}
+
+pub(crate) struct Guard;
+
+impl Guard {
+    /// Register a step that undoes something just created.
+    pub(crate) fn push(&self, step: impl FnOnce() + Send + 'static) {
+        if let Some(steps) = lock().as_mut() {
+            steps.push(Box::new(step));
+        }
+    }
+
+    /// Keep everything: the operation completed.
+    pub(crate) fn commit(self) {
+        lock().take();
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        // Hold the lock while undoing so a signal arriving now waits for us.
+        let mut slot = lock();
+        if let Some(steps) = slot.take() {
+            run(steps);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn install() -> std::io::Result<()> {
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+
+    let mut signals = signal_hook::iterator::Signals::new([SIGINT, SIGTERM, SIGHUP])?;
+    std::thread::Builder::new()
+        .name("pc-interrupt".into())
+        .spawn(move || {
+            for signal in signals.forever() {
+                on_interrupt();
+                // No guard alive: behave as if the handler was never installed.
+                let _ = signal_hook::low_level::emulate_default_handler(signal);
+            }
+        })?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn install() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(|| {
+        on_interrupt();
+        std::process::exit(EXIT_INTERRUPTED);
+    })
+}
+
+/// Roll back and exit if a guard is alive; otherwise return and let the caller
+/// apply the signal's default action.
+fn on_interrupt() {
+    let mut slot = lock();
+    let Some(steps) = slot.take() else {
+        return;
+    };
+    if !steps.is_empty() {
+        eprintln!("Interrupted; rolling back the new agent");
+        run(steps);
+    }
+    std::process::exit(EXIT_INTERRUPTED);
+}
+
+fn run(steps: Vec<Step>) {
+    for step in steps.into_iter().rev() {
+        step();
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Option<Vec<Step>>> {
+    STEPS.lock().unwrap_or_else(|e| e.into_inner())
+}
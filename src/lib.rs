@@ -52,3 +52,157 @@ pub mod agent_name {
         Ok(out)
     }
 }
+
+pub mod paths {
+    use std::path::PathBuf;
+
+    /// Expand a leading `~` / `~user` and `$VAR` / `${VAR}` references in a
+    /// user-supplied path, the way a shell would for an unquoted argument.
+    pub fn expand_path(input: &str) -> Result<PathBuf, String> {
+        let expanded = expand_vars(input)?;
+        Ok(PathBuf::from(expand_tilde(&expanded)?))
+    }
+
+    /// Expand `{name}` placeholders in a relative path template using `lookup`, and check
+    /// that every component of the result is a plain directory name (so the path stays
+    /// below whatever it is joined to).
+    pub fn expand_template(
+        template: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<PathBuf, String> {
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(i) = rest.find('{') {
+            out.push_str(&rest[..i]);
+            let after = &rest[i + 1..];
+            let end = after
+                .find('}')
+                .ok_or_else(|| format!("unterminated {{ in {template:?}"))?;
+            let name = &after[..end];
+            let value = lookup(name)
+                .ok_or_else(|| format!("unknown placeholder {{{name}}} in {template:?}"))?;
+            if value.contains(['/', '\\']) {
+                return Err(format!(
+                    "{{{name}}} expands to {value:?}, which contains a path separator"
+                ));
+            }
+            out.push_str(&value);
+            rest = &after[end + 1..];
+        }
+        out.push_str(rest);
+
+        let path = PathBuf::from(&out);
+        let mut components = 0;
+        for c in path.components() {
+            match c {
+                std::path::Component::Normal(_) => components += 1,
+                _ => return Err(format!("{template:?} expands to {out:?}, which is not a relative path of plain directory names")),
+            }
+        }
+        if components == 0 || out.split('/').any(str::is_empty) {
+            return Err(format!(
+                "{template:?} expands to {out:?}, which has an empty path component"
+            ));
+        }
+        Ok(path)
+    }
+
+    fn expand_tilde(s: &str) -> Result<String, String> {
+        let Some(rest) = s.strip_prefix('~') else {
+            return Ok(s.to_string());
+        };
+        let (user, tail) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, ""),
+        };
+        let home = if user.is_empty() {
+            std::env::var("HOME").map_err(|_| format!("cannot expand {s:?}: HOME is not set"))?
+        } else {
+            home_of_user(user)
+                .ok_or_else(|| format!("cannot expand {s:?}: unknown user {user:?}"))?
+        };
+        Ok(format!("{home}{tail}"))
+    }
+
+    fn home_of_user(user: &str) -> Option<String> {
+        let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+        passwd.lines().find_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            (fields.len() >= 6 && fields[0] == user).then(|| fields[5].to_string())
+        })
+    }
+
+    fn expand_vars(s: &str) -> Result<String, String> {
+        let mut out = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(i) = rest.find('$') {
+            out.push_str(&rest[..i]);
+            let after = &rest[i + 1..];
+            let (name, next) = if let Some(braced) = after.strip_prefix('{') {
+                let end = braced
+                    .find('}')
+                    .ok_or_else(|| format!("unterminated ${{ in {s:?}"))?;
+                (&braced[..end], &braced[end + 1..])
+            } else {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            };
+            if name.is_empty() {
+                out.push('$');
+            } else {
+                let value = std::env::var(name)
+                    .map_err(|_| format!("cannot expand {s:?}: ${name} is not set"))?;
+                out.push_str(&value);
+            }
+            rest = next;
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn expands_variables_and_leaves_plain_paths_alone() {
+            std::env::set_var("PC_TEST_EXPAND_DIR", "/srv/agents");
+            assert_eq!(
+                expand_path("$PC_TEST_EXPAND_DIR/x").unwrap(),
+                PathBuf::from("/srv/agents/x")
+            );
+            assert_eq!(
+                expand_path("${PC_TEST_EXPAND_DIR}-old").unwrap(),
+                PathBuf::from("/srv/agents-old")
+            );
+            assert_eq!(expand_path("a/~b/$").unwrap(), PathBuf::from("a/~b/$"));
+            assert!(expand_path("$PC_TEST_EXPAND_UNSET_VAR").is_err());
+        }
+
+        #[test]
+        fn template_placeholders_must_stay_below_the_root() {
+            let lookup = |name: &str| match name {
+                "agent" => Some("feat_a".to_string()),
+                "user" => Some("..".to_string()),
+                "bad" => Some("a/b".to_string()),
+                _ => None,
+            };
+            assert_eq!(
+                expand_template("2024/{agent}", lookup).unwrap(),
+                PathBuf::from("2024/feat_a")
+            );
+            assert!(expand_template("{user}/{agent}", lookup).is_err());
+            assert!(expand_template("{bad}", lookup).is_err());
+            assert!(expand_template("{nope}", lookup).is_err());
+            assert!(expand_template("/abs/{agent}", lookup).is_err());
+            assert!(expand_template("x//{agent}", lookup).is_err());
+        }
+
+        #[test]
+        fn unknown_user_is_an_error() {
+            assert!(expand_path("~pc-no-such-user/x").is_err());
+        }
+    }
+}
@@ -1,10 +1,26 @@
+use std::process::ExitCode;
+
 mod cli;
 mod commands;
+mod config;
+mod devcontainer;
+mod error;
 mod exec;
 mod git;
+mod i18n;
+mod interrupt;
 mod meta;
+mod output;
+mod preflight;
+mod report;
 mod vscode;
 
-fn main() -> anyhow::Result<()> {
-    crate::cli::run()
+fn main() -> ExitCode {
+    match crate::cli::run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            ExitCode::from(crate::error::exit_code(&e))
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::process::Command;
 
@@ -8,25 +9,212 @@ use serde::{Deserialize, Serialize};
 pub(crate) struct AgentMeta {
     #[serde(default)]
     pub(crate) branch_name: Option<String>,
+    /// Canonical worktree path recorded at creation time.
+    #[serde(default)]
+    pub(crate) worktree_path: Option<PathBuf>,
+    /// Creation time (seconds since the Unix epoch).
+    #[serde(default)]
+    pub(crate) created_at: Option<u64>,
+    /// Files pc wrote into the worktree, deleted again by `pc rm` while unchanged.
+    #[serde(default)]
+    pub(crate) generated_files: Vec<GeneratedFile>,
+    /// User-supplied `--label KEY=VALUE` pairs.
+    #[serde(default)]
+    pub(crate) labels: BTreeMap<String, String>,
+    /// Upstream of the agent branch (`<remote>/<branch>`) at creation time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) upstream: Option<String>,
+    /// `.pc.toml` agent profile selected with `--profile`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) profile: Option<String>,
+    /// Directories checked out with `--sparse` (empty: full checkout).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) sparse_paths: Vec<String>,
+    /// Worktrees of other repositories added with `--companion`, removed with the agent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) companions: Vec<Companion>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "GeneratedFileEntry")]
+pub(crate) struct GeneratedFile {
+    /// Relative to the worktree.
+    pub(crate) path: PathBuf,
+    /// `git hash-object` of the content pc wrote (`None` in metadata from older versions,
+    /// which recorded bare paths; such files are never deleted).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) blob: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GeneratedFileEntry {
+    Path(PathBuf),
+    File {
+        path: PathBuf,
+        #[serde(default)]
+        blob: Option<String>,
+    },
+}
+
+impl From<GeneratedFileEntry> for GeneratedFile {
+    fn from(entry: GeneratedFileEntry) -> Self {
+        match entry {
+            GeneratedFileEntry::Path(path) => Self { path, blob: None },
+            GeneratedFileEntry::File { path, blob } => Self { path, blob },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Companion {
+    /// The companion repository (its main worktree).
+    pub(crate) repo: PathBuf,
+    pub(crate) branch: String,
+    /// Where its worktree was added, inside the agent worktree.
+    pub(crate) worktree_path: PathBuf,
+}
+
+pub(crate) fn now_unix_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Today's UTC date as `(year, month, day)`.
+pub(crate) fn today_utc() -> (i64, u32, u32) {
+    civil_from_days((now_unix_secs() / 86_400) as i64)
+}
+
+/// Days since 1970-01-01 to a proleptic Gregorian date (Howard Hinnant's algorithm).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
 }
 
 fn agent_meta_path(agent_name: &str) -> Result<PathBuf> {
-    let rel = format!("pc/agents/{agent_name}.json");
+    git_path(&format!("pc/agents/{agent_name}.json"))
+}
+
+/// `rel` inside the common git dir, so every worktree of the repo sees the same
+/// metadata (`git rev-parse --git-path` would point into `.git/worktrees/<name>/` from a
+/// linked worktree).
+pub(crate) fn git_path(rel: &str) -> Result<PathBuf> {
+    Ok(common_dir()?.join(rel))
+}
+
+fn common_dir() -> Result<PathBuf> {
     let output = Command::new("git")
-        .args(["rev-parse", "--git-path", &rel])
+        .args(["rev-parse", "--path-format=absolute", "--git-common-dir"])
         .output()
-        .context("Failed to run git rev-parse --git-path")?;
+        .context("Failed to run git rev-parse --git-common-dir")?;
     if !output.status.success() {
-        bail!("git rev-parse --git-path failed");
+        bail!("git rev-parse --git-common-dir failed");
     }
     let s = String::from_utf8(output.stdout).context("git output not utf8")?;
     let p = s.trim();
     if p.is_empty() {
-        bail!("git-path returned empty path for {rel}");
+        bail!("git rev-parse --git-common-dir returned an empty path");
     }
     Ok(PathBuf::from(p))
 }
 
+/// Move metadata written by older versions, which resolved `pc/agents` with
+/// `--git-path` and so stored it per worktree (`<common>/worktrees/<wt>/pc/agents/`
+/// when run from a linked worktree), into the shared directory. Files that already
+/// have a counterpart there are left alone.
+fn migrate_legacy_metas() -> Result<()> {
+    let common = common_dir()?;
+    let Ok(worktrees) = std::fs::read_dir(common.join("worktrees")) else {
+        return Ok(());
+    };
+    let dest_dir = common.join("pc/agents");
+    for wt in worktrees.flatten() {
+        let Ok(entries) = std::fs::read_dir(wt.path().join("pc/agents")) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(file_name) = path.file_name() else {
+                continue;
+            };
+            let dest = dest_dir.join(file_name);
+            if dest.exists() {
+                continue;
+            }
+            std::fs::create_dir_all(&dest_dir)
+                .with_context(|| format!("Failed to create {}", dest_dir.display()))?;
+            std::fs::rename(&path, &dest).with_context(|| {
+                format!("Failed to move {} to {}", path.display(), dest.display())
+            })?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn read_agent_meta(agent_name: &str) -> Result<Option<AgentMeta>> {
+    let path = agent_meta_path(agent_name)?;
+    if !path.is_file() {
+        migrate_legacy_metas()?;
+        if !path.is_file() {
+            return Ok(None);
+        }
+    }
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let meta = serde_json::from_str(&text)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(Some(meta))
+}
+
+/// All recorded agents as `(agent_name, meta)`, sorted by agent name.
+/// Unreadable metadata files are skipped with a warning.
+pub(crate) fn list_agent_metas() -> Result<Vec<(String, AgentMeta)>> {
+    migrate_legacy_metas()?;
+    let dir = git_path("pc/agents")?;
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut out = Vec::new();
+    for entry in
+        std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let path = entry
+            .with_context(|| format!("Failed to read {}", dir.display()))?
+            .path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(agent_name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::new)
+            .and_then(|text| serde_json::from_str::<AgentMeta>(&text).map_err(anyhow::Error::new));
+        match parsed {
+            Ok(meta) => out.push((agent_name.to_string(), meta)),
+            Err(e) => eprintln!(
+                "Warning: skipping unreadable agent metadata {}: {e:#}",
+                path.display()
+            ),
+        }
+    }
+    out.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(out)
+}
+
 pub(crate) fn write_agent_meta(agent_name: &str, meta: AgentMeta) -> Result<()> {
     let path = agent_meta_path(agent_name)?;
     if let Some(parent) = path.parent() {
@@ -34,7 +222,13 @@ pub(crate) fn write_agent_meta(agent_name: &str, meta: AgentMeta) -> Result<()>
             .with_context(|| format!("Failed to create {}", parent.display()))?;
     }
     let text = serde_json::to_string_pretty(&meta)? + "\n";
-    std::fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+    // Write to a sibling temp file and rename so readers never observe a partial file.
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, text).with_context(|| format!("Failed to write {}", tmp.display()))?;
+    if let Err(e) = std::fs::rename(&tmp, &path) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(anyhow::Error::new(e).context(format!("Failed to write {}", path.display())));
+    }
     Ok(())
 }
 
@@ -46,3 +240,36 @@ pub(crate) fn remove_agent_meta(agent_name: &str) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_files_accept_bare_paths_from_older_metadata() {
+        let m: AgentMeta = serde_json::from_str(
+            r#"{"generated_files": [".env", {"path": ".devcontainer/devcontainer.json", "blob": "abc"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            m.generated_files,
+            vec![
+                GeneratedFile {
+                    path: PathBuf::from(".env"),
+                    blob: None,
+                },
+                GeneratedFile {
+                    path: PathBuf::from(".devcontainer/devcontainer.json"),
+                    blob: Some("abc".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn civil_from_days_handles_epoch_and_leap_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(19_783), (2024, 3, 1));
+    }
+}
@@ -0,0 +1,41 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub(crate) fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+pub(crate) fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+pub(crate) fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Informational status line on stdout; suppressed by `--quiet`.
+/// Warnings and errors go to stderr directly and are never suppressed.
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Diagnostic line on stderr, only with `--verbose`.
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::output::is_verbose() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use {debug, info};
@@ -0,0 +1,70 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Result;
+
+use crate::config::RepoConfig;
+use crate::error::bail_git_state;
+use crate::meta;
+
+/// Cheap checks before creating a new agent worktree. Limits come from `.pc.toml`
+/// (`max_agents`, `min_free_mb`); a check whose data can't be obtained only warns.
+pub(crate) fn check_new_agent(
+    config: &RepoConfig,
+    agent_name: &str,
+    base_dir: &Path,
+) -> Result<()> {
+    if let Some(max) = config.max_agents {
+        let existing = meta::list_agent_metas()?
+            .into_iter()
+            .filter(|(name, _)| name != agent_name)
+            .count();
+        if existing >= max {
+            bail_git_state!("Agent limit reached: {existing} agents exist (max_agents = {max} in .pc.toml). Remove one with `pc rm`, or pass --no-preflight."
+            );
+        }
+    }
+
+    if let Some(min_free_mb) = config.min_free_mb {
+        match free_space_mb(base_dir) {
+            Some(free) if free < min_free_mb => bail_git_state!("Not enough free disk space in {}: {free} MB available, {min_free_mb} MB required (min_free_mb in .pc.toml). Pass --no-preflight to skip.",
+                base_dir.display()
+            ),
+            Some(_) => {}
+            None => eprintln!(
+                "Warning: could not determine free disk space for {}; skipping check",
+                base_dir.display()
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// Available space on the filesystem holding `dir`, via POSIX `df -Pk`.
+fn free_space_mb(dir: &Path) -> Option<u64> {
+    let output = Command::new("df").arg("-Pk").arg(dir).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    parse_df_available_kb(&text).map(|kb| kb / 1024)
+}
+
+fn parse_df_available_kb(text: &str) -> Option<u64> {
+    // Header, then: Filesystem 1024-blocks Used Available Capacity Mounted-on
+    let line = text.lines().nth(1)?;
+    line.split_whitespace().nth(3)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_df_available_kb_reads_fourth_column() {
+        let text = "Filesystem     1024-blocks      Used Available Capacity Mounted on\n\
+/dev/sda1        102400000  51200000  40960000      56% /\n";
+        assert_eq!(parse_df_available_kb(text), Some(40_960_000));
+        assert_eq!(parse_df_available_kb("Filesystem\n"), None);
+    }
+}
@@ -0,0 +1,99 @@
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use crate::output;
+
+/// Progress for multi-step commands: numbered step lines and a closing summary.
+pub(crate) trait Reporter {
+    /// Start the next step (finishing the previous one).
+    fn step(&mut self, message: &str);
+    /// Finish the last step and print the summary with next-step hints.
+    fn finish(&mut self, hints: &[String]);
+}
+
+/// Steps slower than this get their elapsed time printed.
+const SLOW_STEP: Duration = Duration::from_secs(1);
+
+/// Writes `[i/n] message` lines to stdout (nothing under `--quiet`).
+pub(crate) struct ConsoleReporter<W: Write> {
+    out: W,
+    total: usize,
+    current: usize,
+    started: Instant,
+    step_started: Option<(Instant, String)>,
+}
+
+impl ConsoleReporter<std::io::Stdout> {
+    pub(crate) fn stdout(total: usize) -> Self {
+        Self::new(std::io::stdout(), total)
+    }
+}
+
+impl<W: Write> ConsoleReporter<W> {
+    pub(crate) fn new(out: W, total: usize) -> Self {
+        Self {
+            out,
+            total,
+            current: 0,
+            started: Instant::now(),
+            step_started: None,
+        }
+    }
+
+    fn end_step(&mut self) {
+        if let Some((started, message)) = self.step_started.take() {
+            let elapsed = started.elapsed();
+            if elapsed >= SLOW_STEP {
+                self.print(&format!("      {message}: {:.1}s", elapsed.as_secs_f64()));
+            }
+        }
+    }
+
+    fn print(&mut self, line: &str) {
+        if !output::is_quiet() {
+            let _ = writeln!(self.out, "{line}");
+        }
+    }
+}
+
+impl<W: Write> Reporter for ConsoleReporter<W> {
+    fn step(&mut self, message: &str) {
+        self.end_step();
+        self.current += 1;
+        self.print(&format!("[{}/{}] {message}", self.current, self.total));
+        self.step_started = Some((Instant::now(), message.to_string()));
+    }
+
+    fn finish(&mut self, hints: &[String]) {
+        self.end_step();
+        self.print(&format!(
+            "Done in {:.1}s",
+            self.started.elapsed().as_secs_f64()
+        ));
+        for hint in hints {
+            self.print(&format!("  {hint}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn console_reporter_numbers_steps_and_prints_hints() {
+        let mut buf = Vec::new();
+        let mut r = ConsoleReporter::new(&mut buf, 2);
+        r.step("Creating worktree");
+        r.step("Recording agent metadata");
+        r.finish(&["pc rm feat/a".to_string()]);
+
+        let text = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "[1/2] Creating worktree");
+        assert_eq!(lines[1], "[2/2] Recording agent metadata");
+        assert!(lines[2].starts_with("Done in "));
+        assert_eq!(lines[3], "  pc rm feat/a");
+        assert_eq!(lines.len(), 4);
+    }
+}
@@ -3,15 +3,37 @@ use std::process::Command;
 
 use anyhow::{bail, Context, Result};
 
+use crate::exec;
+
+/// VS Code's CLI is a `.cmd` shim on Windows, which `Command` won't find as plain `code`.
+#[cfg(windows)]
+const CODE_CANDIDATES: &[&str] = &["code.cmd", "code"];
+#[cfg(not(windows))]
+const CODE_CANDIDATES: &[&str] = &["code"];
+
+fn code_bin() -> Option<&'static str> {
+    CODE_CANDIDATES
+        .iter()
+        .copied()
+        .find(|bin| exec::is_in_path(bin))
+}
+
+pub(crate) fn is_available() -> bool {
+    code_bin().is_some()
+}
+
 pub(crate) fn open_vscode_local(worktree_dir: &Path) -> Result<()> {
-    let status = Command::new("code")
+    let Some(bin) = code_bin() else {
+        bail!("`code` not found in PATH");
+    };
+    let status = Command::new(bin)
         .args(["--new-window"])
         .arg(worktree_dir)
         .status()
-        .context("Failed to spawn `code`")?;
+        .with_context(|| format!("Failed to spawn `{bin}`"))?;
     if status.success() {
         Ok(())
     } else {
-        bail!("`code` failed with status: {status}");
+        bail!("`{bin}` failed with status: {status}");
     }
 }
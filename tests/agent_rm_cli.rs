@@ -122,6 +122,152 @@ mod unix_only {
             .stderr(contains("Agent worktree not found"));
     }
 
+    #[test]
+    fn agent_rm_uses_worktree_path_recorded_in_meta() {
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+
+        let agents = td.path().join("custom-agents");
+        fs::create_dir_all(&agents).unwrap();
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args([
+                "new",
+                "feat/a",
+                "--no-open",
+                "--base-dir",
+                agents.to_str().unwrap(),
+            ])
+            .assert()
+            .success();
+
+        let meta: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(git_path(&repo, "pc/agents/feat_a.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            meta["worktree_path"].as_str().map(Path::new),
+            Some(fs::canonicalize(agents.join("feat_a")).unwrap().as_path())
+        );
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .env_remove("AGENT_WORKTREE_BASE_DIR")
+            .args(["rm", "feat/a"])
+            .assert()
+            .success()
+            .stdout(contains("via agent metadata"));
+
+        assert!(!agents.join("feat_a").exists());
+        assert!(!Path::new(&git_path(&repo, "pc/agents/feat_a.json")).exists());
+    }
+
+    #[test]
+    fn agent_rm_accepts_agent_name_or_branch_name() {
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+
+        let agents = td.path().join("agents");
+        fs::create_dir_all(&agents).unwrap();
+
+        common::pc_new(&repo, &agents, &["feat/a"]);
+        common::pc_new(&repo, &agents, &["feat/b", "--agent-name", "bee"]);
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["rm", "feat_a", "--base-dir", agents.to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(contains("Removed worktree for feat/a"));
+        assert!(!agents.join("feat_a").exists());
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["rm", "feat/b", "--base-dir", agents.to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(contains("Removed worktree for feat/b"));
+        assert!(!agents.join("bee").exists());
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["rm", "feat", "--base-dir", agents.to_str().unwrap()])
+            .assert()
+            .failure()
+            .stderr(contains("Agent worktree not found"));
+    }
+
+    #[test]
+    fn agent_rm_errors_on_ambiguous_agent_argument() {
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+
+        let agents = td.path().join("agents");
+        fs::create_dir_all(&agents).unwrap();
+
+        // `feat_a` is both the agent name of `feat/a` and the branch of agent `other`.
+        common::pc_new(&repo, &agents, &["feat/a"]);
+        common::pc_new(&repo, &agents, &["feat_a", "--agent-name", "other"]);
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["rm", "feat_a", "--base-dir", agents.to_str().unwrap()])
+            .assert()
+            .failure()
+            .stderr(contains("Ambiguous"));
+        assert!(agents.join("feat_a").exists());
+        assert!(agents.join("other").exists());
+    }
+
+    #[test]
+    fn agent_rm_migrates_metadata_from_the_old_per_worktree_location() {
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+        let agents = td.path().join("custom-agents");
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args([
+                "new",
+                "feat/a",
+                "--no-open",
+                "--base-dir",
+                agents.to_str().unwrap(),
+            ])
+            .assert()
+            .success();
+
+        // Older versions resolved the metadata path with `--git-path`, which lands in
+        // `.git/worktrees/<wt>/` when pc ran from a linked worktree.
+        let other = td.path().join("other");
+        common::run_git(
+            &repo,
+            &["worktree", "add", "-q", "--detach", other.to_str().unwrap()],
+        );
+        let shared = git_path(&repo, "pc/agents/feat_a.json");
+        let legacy = git_path(&other, "pc/agents/feat_a.json");
+        assert_ne!(shared, legacy);
+        fs::create_dir_all(Path::new(&legacy).parent().unwrap()).unwrap();
+        fs::rename(&shared, &legacy).unwrap();
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .env_remove("AGENT_WORKTREE_BASE_DIR")
+            .args(["rm", "feat/a"])
+            .assert()
+            .success()
+            .stdout(contains("via agent metadata"));
+
+        assert!(!agents.join("feat_a").exists());
+        assert!(!Path::new(&shared).exists());
+        assert!(!Path::new(&legacy).exists());
+    }
+
     #[test]
     fn agent_rm_without_args_requires_tty_or_branch_name() {
         let td = TempDir::new().unwrap();
@@ -135,4 +281,228 @@ mod unix_only {
             .failure()
             .stderr(contains("No worktree specified and no TTY available"));
     }
+
+    #[test]
+    fn agent_rm_by_agent_name_after_branch_was_deleted() {
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+
+        let agents = td.path().join("agents");
+        fs::create_dir_all(&agents).unwrap();
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args([
+                "new",
+                "feat/a",
+                "--no-open",
+                "--base-dir",
+                agents.to_str().unwrap(),
+            ])
+            .assert()
+            .success();
+
+        common::run_git(&agents.join("feat_a"), &["checkout", "-q", "--detach"]);
+        common::run_git(&repo, &["branch", "-D", "feat/a"]);
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .arg("list")
+            .assert()
+            .success()
+            .stderr(contains("branch feat/a of agent feat_a no longer exists"));
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["rm", "feat_a", "--base-dir", agents.to_str().unwrap()])
+            .assert()
+            .success()
+            .stderr(contains("branch feat/a no longer exists"))
+            .stderr(contains("detached HEAD"));
+
+        assert!(!agents.join("feat_a").exists());
+        assert!(!Path::new(&git_path(&repo, "pc/agents/feat_a.json")).exists());
+    }
+
+    #[test]
+    fn agent_rm_by_worktree_path() {
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+
+        let agents = td.path().join("agents");
+        fs::create_dir_all(&agents).unwrap();
+
+        for branch in ["feat/a", "feat/b"] {
+            Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+                .current_dir(&repo)
+                .args(["new", branch, "--no-open", "--base-dir"])
+                .arg(&agents)
+                .assert()
+                .success();
+        }
+
+        // A path given from the main worktree.
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .arg("rm")
+            .arg(agents.join("feat_a"))
+            .assert()
+            .success()
+            .stdout(contains("Removed worktree for feat/a"));
+        assert!(!agents.join("feat_a").exists());
+        assert!(!Path::new(&git_path(&repo, "pc/agents/feat_a.json")).exists());
+
+        // `.` from inside the worktree itself.
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(agents.join("feat_b"))
+            .args(["rm", "."])
+            .assert()
+            .success()
+            .stdout(contains("Removed worktree for feat/b"));
+        assert!(!agents.join("feat_b").exists());
+        assert!(!Path::new(&git_path(&repo, "pc/agents/feat_b.json")).exists());
+        assert!(git_show_ref(&repo, "refs/heads/feat/b"));
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["rm", "."])
+            .assert()
+            .code(3)
+            .stderr(contains("Not an agent worktree"));
+    }
+
+    #[test]
+    fn agent_rm_ignore_patterns_avoid_force() {
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+
+        let agents = td.path().join("agents");
+        for branch in ["feat/a", "feat/b"] {
+            Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+                .current_dir(&repo)
+                .args(["new", branch, "--no-open", "--base-dir"])
+                .arg(&agents)
+                .assert()
+                .success();
+        }
+        for agent in ["feat_a", "feat_b"] {
+            fs::create_dir_all(agents.join(agent).join(".next")).unwrap();
+            fs::write(agents.join(agent).join(".next").join("cache"), "x").unwrap();
+            fs::create_dir_all(agents.join(agent).join("dist")).unwrap();
+            fs::write(agents.join(agent).join("dist").join("app.js"), "x").unwrap();
+        }
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["rm", "feat/a", "--ignore", ".next/", "--base-dir"])
+            .arg(&agents)
+            .assert()
+            .code(7);
+        assert!(agents.join("feat_a").exists());
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["rm", "feat/a", "--ignore", ".next/", "--ignore", "dist/"])
+            .arg("--base-dir")
+            .arg(&agents)
+            .assert()
+            .success();
+        assert!(!agents.join("feat_a").exists());
+
+        // The patterns only applied to that removal: nothing was added to the shared
+        // info/exclude, so feat_b's build output still shows up as untracked.
+        let exclude = common::git_path(&repo, "info/exclude");
+        let exclude = fs::read_to_string(exclude).unwrap_or_default();
+        assert!(
+            !exclude.contains(".next/") && !exclude.contains("dist/"),
+            "{exclude}"
+        );
+        let status = StdCommand::new("git")
+            .current_dir(agents.join("feat_b"))
+            .args(["status", "--porcelain", "--untracked-files=all"])
+            .output()
+            .unwrap();
+        let status = String::from_utf8(status.stdout).unwrap();
+        assert!(
+            status.contains(".next/cache") && status.contains("dist/app.js"),
+            "{status}"
+        );
+
+        fs::write(
+            repo.join(".pc.toml"),
+            "rm_ignore = [\".next/\", \"dist/\"]\n",
+        )
+        .unwrap();
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["rm", "feat/b", "--base-dir"])
+            .arg(&agents)
+            .assert()
+            .success();
+        assert!(!agents.join("feat_b").exists());
+    }
+
+    #[test]
+    fn agent_new_lock_is_listed_and_rm_unlocks() {
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+        let agents = td.path().join("agents");
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["new", "feat/a", "--no-open", "--lock", "on usb disk"])
+            .arg("--base-dir")
+            .arg(&agents)
+            .assert()
+            .success();
+        let porcelain = StdCommand::new("git")
+            .current_dir(&repo)
+            .args(["worktree", "list", "--porcelain"])
+            .output()
+            .unwrap();
+        assert!(String::from_utf8_lossy(&porcelain.stdout).contains("locked on usb disk"));
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .arg("list")
+            .assert()
+            .success()
+            .stdout(contains("[locked]"));
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["list", "--json"])
+            .assert()
+            .success()
+            .stdout(contains("\"locked\": true"));
+
+        // A removal refused for local changes keeps the lock.
+        fs::write(agents.join("feat_a").join("wip.txt"), "x").unwrap();
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["rm", "feat/a", "--base-dir"])
+            .arg(&agents)
+            .assert()
+            .code(7);
+        let porcelain = StdCommand::new("git")
+            .current_dir(&repo)
+            .args(["worktree", "list", "--porcelain"])
+            .output()
+            .unwrap();
+        assert!(String::from_utf8_lossy(&porcelain.stdout).contains("locked on usb disk"));
+        fs::remove_file(agents.join("feat_a").join("wip.txt")).unwrap();
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["rm", "feat/a", "--base-dir"])
+            .arg(&agents)
+            .assert()
+            .success()
+            .stdout(contains("Unlocked worktree"));
+        assert!(!agents.join("feat_a").exists());
+        assert!(git_show_ref(&repo, "refs/heads/feat/a"));
+    }
 }
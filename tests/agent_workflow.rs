@@ -2,41 +2,27 @@ use std::path::Path;
 use std::process::Command as StdCommand;
 
 use assert_cmd::Command;
+use predicates::prelude::PredicateBooleanExt;
 use tempfile::TempDir;
 
-fn run_git(repo: &Path, args: &[&str]) {
-    let status = StdCommand::new("git")
-        .current_dir(repo)
+#[path = "common/mod.rs"]
+mod common;
+
+fn git_stdout(dir: &Path, args: &[&str]) -> String {
+    let out = StdCommand::new("git")
+        .current_dir(dir)
         .args(args)
-        .status()
+        .output()
         .expect("spawn git");
-    assert!(status.success(), "git {:?} failed", args);
-}
-
-fn init_repo(repo: &Path) {
-    std::fs::create_dir_all(repo).unwrap();
-    run_git(repo, &["init", "-b", "main"]);
-    std::fs::write(repo.join("README.md"), "hello\n").unwrap();
-    run_git(repo, &["add", "-A"]);
-    run_git(
-        repo,
-        &[
-            "-c",
-            "user.name=pc-test",
-            "-c",
-            "user.email=pc-test@example.com",
-            "commit",
-            "-m",
-            "init",
-        ],
-    );
+    assert!(out.status.success(), "git {:?} failed", args);
+    String::from_utf8_lossy(&out.stdout).trim().to_string()
 }
 
 #[test]
 fn agent_new_and_rm_clean_should_not_require_force() {
     let td = TempDir::new().unwrap();
     let repo = td.path().join("repo");
-    init_repo(&repo);
+    common::init_repo(&repo);
 
     let agents = td.path().join("agents");
     std::fs::create_dir_all(&agents).unwrap();
@@ -76,7 +62,7 @@ fn agent_new_and_rm_clean_should_not_require_force() {
 fn agent_rm_without_force_should_fail_if_user_left_untracked_files() {
     let td = TempDir::new().unwrap();
     let repo = td.path().join("repo");
-    init_repo(&repo);
+    common::init_repo(&repo);
 
     let agents = td.path().join("agents");
     std::fs::create_dir_all(&agents).unwrap();
@@ -103,11 +89,33 @@ fn agent_rm_without_force_should_fail_if_user_left_untracked_files() {
         .failure();
 }
 
+/// Commit a `.pc.toml` that opts into treating `.venv/` and `node_modules/` as disposable.
+fn write_rm_ignore_config(repo: &Path) {
+    std::fs::write(
+        repo.join(".pc.toml"),
+        "rm_ignore = [\".venv/\", \"node_modules/\"]\n",
+    )
+    .unwrap();
+    common::run_git(repo, &["add", ".pc.toml"]);
+    common::run_git(
+        repo,
+        &[
+            "-c",
+            "user.name=pc-test",
+            "-c",
+            "user.email=pc-test@example.com",
+            "commit",
+            "-m",
+            "pc config",
+        ],
+    );
+}
+
 #[test]
 fn agent_rm_should_succeed_with_common_generated_dirs() {
     let td = TempDir::new().unwrap();
     let repo = td.path().join("repo");
-    init_repo(&repo);
+    common::init_repo(&repo);
 
     let agents = td.path().join("agents");
     std::fs::create_dir_all(&agents).unwrap();
@@ -130,6 +138,15 @@ fn agent_rm_should_succeed_with_common_generated_dirs() {
     std::fs::create_dir_all(worktree.join("node_modules")).unwrap();
     std::fs::write(worktree.join("node_modules").join(".keep"), "x").unwrap();
 
+    // Build output is only disposable once the repo opts in.
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["rm", "agent-a", "--base-dir", agents.to_str().unwrap()])
+        .assert()
+        .code(7);
+    assert!(worktree.exists());
+
+    write_rm_ignore_config(&repo);
     Command::new(assert_cmd::cargo::cargo_bin!("pc"))
         .current_dir(&repo)
         .args(["rm", "agent-a", "--base-dir", agents.to_str().unwrap()])
@@ -143,7 +160,7 @@ fn agent_rm_should_succeed_with_common_generated_dirs() {
 fn agent_new_should_open_existing_worktree_when_it_already_exists() {
     let td = TempDir::new().unwrap();
     let repo = td.path().join("repo");
-    init_repo(&repo);
+    common::init_repo(&repo);
 
     let agents = td.path().join("agents");
     std::fs::create_dir_all(&agents).unwrap();
@@ -186,7 +203,7 @@ fn parse_worktree_from_stdout(stdout: &[u8]) -> std::path::PathBuf {
 fn agent_new_accepts_branch_names_with_slash() {
     let td = TempDir::new().unwrap();
     let repo = td.path().join("repo");
-    init_repo(&repo);
+    common::init_repo(&repo);
 
     let agents = td.path().join("agents");
     std::fs::create_dir_all(&agents).unwrap();
@@ -233,7 +250,7 @@ fn agent_new_accepts_branch_names_with_slash() {
 fn top_level_new_creates_worktree_and_branch() {
     let td = TempDir::new().unwrap();
     let repo = td.path().join("repo");
-    init_repo(&repo);
+    common::init_repo(&repo);
 
     let agents = td.path().join("agents");
     std::fs::create_dir_all(&agents).unwrap();
@@ -266,3 +283,291 @@ fn top_level_new_creates_worktree_and_branch() {
         .unwrap();
     assert!(status.success(), "branch should exist");
 }
+
+#[test]
+fn agent_new_recreate_replaces_worktree_and_keeps_branch() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+
+    let agents = td.path().join("agents");
+    std::fs::create_dir_all(&agents).unwrap();
+
+    let new_args = [
+        "new",
+        "agent-a",
+        "--no-open",
+        "--base-dir",
+        agents.to_str().unwrap(),
+    ];
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(new_args)
+        .assert()
+        .success();
+
+    write_rm_ignore_config(&repo);
+    let worktree = agents.join("agent-a");
+    std::fs::write(worktree.join("work.txt"), "x").unwrap();
+    common::run_git(&worktree, &["add", "-A"]);
+    common::run_git(
+        &worktree,
+        &[
+            "-c",
+            "user.name=pc-test",
+            "-c",
+            "user.email=pc-test@example.com",
+            "commit",
+            "-m",
+            "work",
+        ],
+    );
+    std::fs::create_dir_all(worktree.join(".venv")).unwrap();
+    std::fs::write(worktree.join(".venv").join("pyvenv.cfg"), "x").unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(new_args)
+        .arg("--recreate")
+        .assert()
+        .success();
+
+    assert!(
+        worktree.join("work.txt").exists(),
+        "recreated worktree should check out the existing branch"
+    );
+    assert!(
+        !worktree.join(".venv").exists(),
+        "recreated worktree should start fresh"
+    );
+}
+
+#[test]
+fn agent_new_recreate_refuses_dirty_worktree_without_force() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+
+    let agents = td.path().join("agents");
+    std::fs::create_dir_all(&agents).unwrap();
+
+    let new_args = [
+        "new",
+        "agent-a",
+        "--no-open",
+        "--base-dir",
+        agents.to_str().unwrap(),
+    ];
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(new_args)
+        .assert()
+        .success();
+
+    let worktree = agents.join("agent-a");
+    std::fs::write(worktree.join("leftover.txt"), "x").unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(new_args)
+        .arg("--recreate")
+        .assert()
+        .code(7)
+        .stderr(predicates::str::contains("uncommitted changes"));
+    assert!(worktree.join("leftover.txt").exists());
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(new_args)
+        .args(["--recreate", "--force"])
+        .assert()
+        .success();
+    assert!(worktree.exists());
+    assert!(!worktree.join("leftover.txt").exists());
+}
+
+#[test]
+fn agent_new_idempotent_rerun_succeeds_without_warning() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+
+    let agents = td.path().join("agents");
+    std::fs::create_dir_all(&agents).unwrap();
+
+    let new_args = [
+        "new",
+        "feat/x",
+        "--no-open",
+        "--idempotent",
+        "--base-dir",
+        agents.to_str().unwrap(),
+    ];
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(new_args)
+        .assert()
+        .success();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(new_args)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Worktree: "))
+        .stderr(predicates::str::contains("Warning").not());
+
+    // A plain directory squatting on the agent path is not a consistent worktree.
+    std::fs::create_dir_all(agents.join("feat_y")).unwrap();
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args([
+            "new",
+            "feat/y",
+            "--no-open",
+            "--idempotent",
+            "--base-dir",
+            agents.to_str().unwrap(),
+        ])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("not a git worktree"));
+}
+
+#[test]
+fn agent_rm_deletes_only_files_pc_generated() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+
+    let agents = td.path().join("agents");
+    std::fs::create_dir_all(&agents).unwrap();
+
+    let template = td.path().join("tpl");
+    std::fs::create_dir_all(&template).unwrap();
+    std::fs::write(template.join("devcontainer.json"), "{}\n").unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args([
+            "new",
+            "agent-a",
+            "--no-open",
+            "--base-dir",
+            agents.to_str().unwrap(),
+            "--template-dir",
+            template.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    let worktree = agents.join("agent-a");
+    let user_file = worktree.join(".devcontainer").join("notes.txt");
+    std::fs::write(&user_file, "mine").unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["rm", "agent-a", "--base-dir", agents.to_str().unwrap()])
+        .assert()
+        .failure();
+    assert!(user_file.exists(), "user-created file must be kept");
+    let generated = worktree.join(".devcontainer").join("devcontainer.json");
+    assert!(generated.exists(), "a refused rm must not delete anything");
+
+    // An edited generated file is the user's now: it blocks rm and --recreate alike.
+    std::fs::remove_file(&user_file).unwrap();
+    std::fs::write(&generated, "{\"edited\": true}\n").unwrap();
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["rm", "agent-a", "--base-dir", agents.to_str().unwrap()])
+        .assert()
+        .code(7);
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "agent-a", "--no-open", "--recreate", "--base-dir"])
+        .arg(&agents)
+        .assert()
+        .code(7);
+    assert_eq!(
+        std::fs::read_to_string(&generated).unwrap(),
+        "{\"edited\": true}\n"
+    );
+
+    std::fs::write(&generated, "{}\n").unwrap();
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["rm", "agent-a", "--base-dir", agents.to_str().unwrap()])
+        .assert()
+        .success();
+    assert!(!worktree.exists());
+}
+
+#[test]
+fn agent_new_handles_uncommitted_changes_in_current_worktree() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    std::fs::write(repo.join("README.md"), "edited\n").unwrap();
+    std::fs::create_dir_all(repo.join("notes")).unwrap();
+    std::fs::write(repo.join("notes/sketch.txt"), "idea\n").unwrap();
+
+    let agents = td.path().join("agents");
+    let pc_new = |branch: &str| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pc"));
+        cmd.current_dir(&repo)
+            .env("GIT_AUTHOR_NAME", "pc-test")
+            .env("GIT_AUTHOR_EMAIL", "pc-test@example.com")
+            .env("GIT_COMMITTER_NAME", "pc-test")
+            .env("GIT_COMMITTER_EMAIL", "pc-test@example.com")
+            .args(["new", branch, "--no-open", "--base-dir"])
+            .arg(&agents);
+        cmd
+    };
+
+    pc_new("plain")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("2 uncommitted change(s)"));
+    assert_eq!(
+        std::fs::read_to_string(agents.join("plain/README.md")).unwrap(),
+        "hello\n"
+    );
+
+    pc_new("copied").arg("--copy-wip").assert().success();
+    assert_eq!(
+        std::fs::read_to_string(agents.join("copied/README.md")).unwrap(),
+        "edited\n"
+    );
+    assert!(agents.join("copied/notes/sketch.txt").is_file());
+
+    let head_before = git_stdout(&repo, &["rev-parse", "HEAD"]);
+    pc_new("wip").arg("--include-wip").assert().success();
+    assert_eq!(git_stdout(&repo, &["rev-parse", "HEAD"]), head_before);
+    assert_eq!(
+        git_stdout(&agents.join("wip"), &["rev-parse", "HEAD^"]),
+        head_before
+    );
+    assert!(git_stdout(&agents.join("wip"), &["status", "--porcelain"]).is_empty());
+    assert!(agents.join("wip/notes/sketch.txt").is_file());
+    // The current worktree keeps its uncommitted changes.
+    assert_eq!(
+        std::fs::read_to_string(repo.join("README.md")).unwrap(),
+        "edited\n"
+    );
+    assert!(!git_stdout(&repo, &["status", "--porcelain"]).is_empty());
+
+    // `--count` makes one WIP commit and starts every agent from it.
+    let out = pc_new("exp")
+        .args(["--count", "2", "--include-wip"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert_eq!(stdout.matches("WIP commit:").count(), 1, "{stdout}");
+    let wip_commit = git_stdout(&repo, &["rev-parse", "exp-1"]);
+    assert_eq!(git_stdout(&repo, &["rev-parse", "exp-2"]), wip_commit);
+    assert_eq!(
+        git_stdout(&repo, &["rev-parse", &format!("{wip_commit}^")]),
+        head_before
+    );
+}
@@ -35,6 +35,16 @@ pub fn init_repo(repo: &Path) {
     );
 }
 
+/// `pc new --no-open --base-dir <agents> <extra>...`, which must succeed.
+pub fn pc_new(repo: &Path, agents: &Path, extra: &[&str]) {
+    assert_cmd::Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(repo)
+        .args(["new", "--no-open", "--base-dir", agents.to_str().unwrap()])
+        .args(extra)
+        .assert()
+        .success();
+}
+
 #[cfg(unix)]
 pub fn write_executable(dir: &Path, name: &str, script: &str) -> PathBuf {
     let path = dir.join(name);
@@ -45,7 +55,19 @@ pub fn write_executable(dir: &Path, name: &str, script: &str) -> PathBuf {
     path
 }
 
-pub fn prepend_path(stub_bin: &Path) -> String {
-    let old = std::env::var("PATH").unwrap_or_default();
-    format!("{}:{}", stub_bin.display(), old)
+pub fn prepend_path(stub_bin: &Path) -> std::ffi::OsString {
+    let old = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![stub_bin.to_path_buf()];
+    paths.extend(std::env::split_paths(&old));
+    std::env::join_paths(paths).unwrap()
+}
+
+pub fn git_path(repo: &Path, rel: &str) -> PathBuf {
+    let out = StdCommand::new("git")
+        .current_dir(repo)
+        .args(["rev-parse", "--path-format=absolute", "--git-path", rel])
+        .output()
+        .expect("spawn git rev-parse --git-path");
+    assert!(out.status.success());
+    PathBuf::from(String::from_utf8_lossy(&out.stdout).trim())
 }
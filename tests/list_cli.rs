@@ -0,0 +1,83 @@
+use assert_cmd::Command;
+use predicates::prelude::PredicateBooleanExt;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+
+#[test]
+fn list_filters_by_branch_glob_and_labels() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let agents = td.path().join("agents");
+
+    common::pc_new(&repo, &agents, &["feat/a", "--label", "team=core"]);
+    common::pc_new(&repo, &agents, &["feat/b", "--label", "team=web"]);
+    common::pc_new(&repo, &agents, &["fix/c", "--label", "team=core"]);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(
+            contains("feat_a")
+                .and(contains("feat_b"))
+                .and(contains("fix_c")),
+        );
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["list", "--branch", "feat/*", "--label", "team=core"])
+        .assert()
+        .success()
+        .stdout(
+            contains("feat_a")
+                .and(contains("team=core"))
+                .and(contains("feat_b").not())
+                .and(contains("fix_c").not()),
+        );
+
+    let out = Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["list", "--json", "--label", "team=core"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let listed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let names: Vec<&str> = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["agent_name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["feat_a", "fix_c"]);
+    assert_eq!(listed[0]["branch_name"], "feat/a");
+    assert_eq!(listed[0]["labels"]["team"], "core");
+}
+
+#[test]
+fn list_plain_format_is_tab_separated() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let agents = td.path().join("agents");
+
+    common::pc_new(&repo, &agents, &["feat/a", "--label", "team=core"]);
+
+    let out = Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["list", "--format", "plain"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let text = String::from_utf8(out.stdout).unwrap();
+    let fields: Vec<&str> = text.trim_end_matches('\n').split('\t').collect();
+    assert_eq!(fields.len(), 4);
+    assert_eq!(fields[0], "feat_a");
+    assert_eq!(fields[1], "feat/a");
+    assert!(fields[2].ends_with("feat_a"));
+    assert_eq!(fields[3], "team=core");
+}
@@ -1,54 +1,17 @@
+#[cfg(unix)]
+#[path = "common/mod.rs"]
+mod common;
+
 #[cfg(unix)]
 mod unix_only {
     use std::fs;
-    use std::os::unix::fs::PermissionsExt;
-    use std::path::{Path, PathBuf};
+    use std::path::PathBuf;
     use std::process::Command as StdCommand;
 
     use assert_cmd::Command;
     use tempfile::TempDir;
 
-    fn run_git(repo: &Path, args: &[&str]) {
-        let status = StdCommand::new("git")
-            .current_dir(repo)
-            .args(args)
-            .status()
-            .expect("spawn git");
-        assert!(status.success(), "git {:?} failed", args);
-    }
-
-    fn init_repo(repo: &Path) {
-        fs::create_dir_all(repo).unwrap();
-        run_git(repo, &["init", "-b", "main"]);
-        fs::write(repo.join("README.md"), "hello\n").unwrap();
-        run_git(repo, &["add", "-A"]);
-        run_git(
-            repo,
-            &[
-                "-c",
-                "user.name=pc-test",
-                "-c",
-                "user.email=pc-test@example.com",
-                "commit",
-                "-m",
-                "init",
-            ],
-        );
-    }
-
-    fn write_executable(dir: &Path, name: &str, script: &str) -> PathBuf {
-        let path = dir.join(name);
-        fs::write(&path, script).unwrap();
-        let mut perms = fs::metadata(&path).unwrap().permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(&path, perms).unwrap();
-        path
-    }
-
-    fn prepend_path(stub_bin: &Path) -> String {
-        let old = std::env::var("PATH").unwrap_or_default();
-        format!("{}:{}", stub_bin.display(), old)
-    }
+    use super::common;
 
     fn parse_worktree_from_stdout(stdout: &[u8]) -> PathBuf {
         let s = String::from_utf8_lossy(stdout);
@@ -63,7 +26,7 @@ mod unix_only {
     fn agent_new_opens_vscode_with_local_worktree_folder() {
         let td = TempDir::new().unwrap();
         let repo = td.path().join("repo");
-        init_repo(&repo);
+        common::init_repo(&repo);
 
         let agents = td.path().join("agents");
         fs::create_dir_all(&agents).unwrap();
@@ -72,7 +35,7 @@ mod unix_only {
         fs::create_dir_all(&stub_bin).unwrap();
         let code_log = td.path().join("code.log");
 
-        write_executable(
+        common::write_executable(
             &stub_bin,
             "code",
             r#"#!/bin/sh
@@ -88,7 +51,7 @@ exit 0
         let output = Command::new(assert_cmd::cargo::cargo_bin!("pc"))
             .current_dir(&repo)
             .env("PC_CODE_LOG", &code_log)
-            .env("PATH", prepend_path(&stub_bin))
+            .env("PATH", common::prepend_path(&stub_bin))
             .args(["new", "agent-a", "--base-dir", agents.to_str().unwrap()])
             .output()
             .unwrap();
@@ -118,7 +81,7 @@ exit 0
     fn agent_new_rolls_back_worktree_and_branch_when_meta_write_fails() {
         let td = TempDir::new().unwrap();
         let repo = td.path().join("repo");
-        init_repo(&repo);
+        common::init_repo(&repo);
 
         let agents = td.path().join("agents");
         fs::create_dir_all(&agents).unwrap();
@@ -166,4 +129,391 @@ exit 0
             "newly-created branch should be rolled back on failure"
         );
     }
+
+    #[test]
+    fn agent_new_rolls_back_when_interrupted_after_worktree_add() {
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+
+        let agents = td.path().join("agents");
+        fs::create_dir_all(&agents).unwrap();
+
+        // Wrap git so `worktree add` completes, signals the test, and then hangs.
+        let real_git = String::from_utf8(
+            StdCommand::new("sh")
+                .args(["-c", "command -v git"])
+                .output()
+                .unwrap()
+                .stdout,
+        )
+        .unwrap();
+        let stub_bin = td.path().join("bin");
+        fs::create_dir_all(&stub_bin).unwrap();
+        let marker = td.path().join("worktree-added");
+        common::write_executable(
+            &stub_bin,
+            "git",
+            &format!(
+                r#"#!/bin/sh
+"{real}" "$@" || exit $?
+if [ "$1" = "worktree" ] && [ "$2" = "add" ]; then
+  touch "{marker}"
+  sleep 30
+fi
+"#,
+                real = real_git.trim(),
+                marker = marker.display()
+            ),
+        );
+
+        let mut child = StdCommand::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .env("PATH", common::prepend_path(&stub_bin))
+            .args([
+                "new",
+                "agent-a",
+                "--no-open",
+                "--base-dir",
+                agents.to_str().unwrap(),
+            ])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(20);
+        while !marker.exists() {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "git worktree add never ran"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        let status = StdCommand::new("kill")
+            .args(["-INT", &child.id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let status = child.wait().unwrap();
+        assert_eq!(status.code(), Some(130));
+
+        assert!(
+            !agents.join("agent-a").exists(),
+            "worktree dir should be removed after Ctrl-C"
+        );
+        let status = StdCommand::new("git")
+            .current_dir(&repo)
+            .args(["show-ref", "--verify", "--quiet", "refs/heads/agent-a"])
+            .status()
+            .unwrap();
+        assert!(
+            !status.success(),
+            "newly-created branch should be rolled back after Ctrl-C"
+        );
+    }
+
+    #[test]
+    fn agent_new_interrupted_after_commit_keeps_agent_and_dies_by_signal() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+        let agents = td.path().join("agents");
+
+        // Wrap git so the post-commit `git push` signals the test and hangs.
+        let real_git = String::from_utf8(
+            StdCommand::new("sh")
+                .args(["-c", "command -v git"])
+                .output()
+                .unwrap()
+                .stdout,
+        )
+        .unwrap();
+        let stub_bin = td.path().join("bin");
+        fs::create_dir_all(&stub_bin).unwrap();
+        let marker = td.path().join("pushing");
+        common::write_executable(
+            &stub_bin,
+            "git",
+            &format!(
+                r#"#!/bin/sh
+if [ "$1" = "push" ]; then
+  touch "{marker}"
+  sleep 30
+fi
+exec "{real}" "$@"
+"#,
+                real = real_git.trim(),
+                marker = marker.display()
+            ),
+        );
+
+        let mut child = StdCommand::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .env("PATH", common::prepend_path(&stub_bin))
+            .args([
+                "new",
+                "agent-a",
+                "--no-open",
+                "--push-upstream",
+                "origin",
+                "--base-dir",
+                agents.to_str().unwrap(),
+            ])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(20);
+        while !marker.exists() {
+            assert!(std::time::Instant::now() < deadline, "git push never ran");
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        let status = StdCommand::new("kill")
+            .args(["-INT", &child.id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let status = child.wait().unwrap();
+        assert_eq!(status.code(), None, "{status:?}");
+        assert_eq!(
+            status.signal(),
+            Some(2),
+            "SIGINT should keep its default action"
+        );
+
+        assert!(
+            agents.join("agent-a").exists(),
+            "committed worktree should stay"
+        );
+        let status = StdCommand::new("git")
+            .current_dir(&repo)
+            .args(["show-ref", "--verify", "--quiet", "refs/heads/agent-a"])
+            .status()
+            .unwrap();
+        assert!(status.success(), "committed branch should stay");
+    }
+
+    #[test]
+    fn agent_new_interrupted_while_adding_companions_removes_them() {
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+        let companion = td.path().join("frontend");
+        common::init_repo(&companion);
+        common::run_git(&companion, &["branch", "feat/x"]);
+        let agents = td.path().join("agents");
+
+        // Wrap git so the companion's `worktree add` completes, signals the test, and hangs.
+        let real_git = String::from_utf8(
+            StdCommand::new("sh")
+                .args(["-c", "command -v git"])
+                .output()
+                .unwrap()
+                .stdout,
+        )
+        .unwrap();
+        let stub_bin = td.path().join("bin");
+        fs::create_dir_all(&stub_bin).unwrap();
+        let marker = td.path().join("companion-added");
+        common::write_executable(
+            &stub_bin,
+            "git",
+            &format!(
+                r#"#!/bin/sh
+"{real}" "$@" || exit $?
+case "$*" in
+  *"worktree add"*.pc-companions*)
+    touch "{marker}"
+    sleep 30
+    ;;
+esac
+"#,
+                real = real_git.trim(),
+                marker = marker.display()
+            ),
+        );
+
+        let mut child = StdCommand::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .env("PATH", common::prepend_path(&stub_bin))
+            .args(["new", "feat/x", "--no-open", "--companion"])
+            .arg(format!("{}#feat/x", companion.display()))
+            .arg("--base-dir")
+            .arg(&agents)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(20);
+        while !marker.exists() {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "companion worktree add never ran"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        let status = StdCommand::new("kill")
+            .args(["-INT", &child.id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(child.wait().unwrap().code(), Some(130));
+
+        assert!(!agents.join("feat_x").exists());
+        let list = StdCommand::new("git")
+            .current_dir(&companion)
+            .args(["worktree", "list", "--porcelain"])
+            .output()
+            .unwrap();
+        let list = String::from_utf8_lossy(&list.stdout);
+        assert_eq!(
+            list.matches("worktree ").count(),
+            1,
+            "companion repo should only list its main worktree:\n{list}"
+        );
+    }
+
+    #[test]
+    fn agent_new_sparse_failure_removes_worktree_and_branch() {
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+        fs::create_dir_all(repo.join("svc")).unwrap();
+        let agents = td.path().join("agents");
+
+        let real_git = String::from_utf8(
+            StdCommand::new("sh")
+                .args(["-c", "command -v git"])
+                .output()
+                .unwrap()
+                .stdout,
+        )
+        .unwrap();
+        let stub_bin = td.path().join("bin");
+        fs::create_dir_all(&stub_bin).unwrap();
+        common::write_executable(
+            &stub_bin,
+            "git",
+            &format!(
+                r#"#!/bin/sh
+case "$*" in
+  *sparse-checkout*) echo "sparse-checkout broken" >&2; exit 1 ;;
+esac
+exec "{real}" "$@"
+"#,
+                real = real_git.trim()
+            ),
+        );
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .env("PATH", common::prepend_path(&stub_bin))
+            .args([
+                "new",
+                "agent-a",
+                "--no-open",
+                "--sparse",
+                "svc",
+                "--base-dir",
+            ])
+            .arg(&agents)
+            .assert()
+            .failure();
+
+        assert!(!agents.join("agent-a").exists());
+        let status = StdCommand::new("git")
+            .current_dir(&repo)
+            .args(["show-ref", "--verify", "--quiet", "refs/heads/agent-a"])
+            .status()
+            .unwrap();
+        assert!(!status.success(), "branch should be removed again");
+        let list = StdCommand::new("git")
+            .current_dir(&repo)
+            .args(["worktree", "list", "--porcelain"])
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&list.stdout)
+                .matches("worktree ")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn agent_rm_relocks_when_git_worktree_remove_fails() {
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+        let agents = td.path().join("agents");
+        let template = td.path().join("tpl");
+        fs::create_dir_all(&template).unwrap();
+        fs::write(template.join("devcontainer.json"), "{}\n").unwrap();
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["new", "agent-a", "--no-open", "--lock", "nfs", "--base-dir"])
+            .arg(&agents)
+            .arg("--template-dir")
+            .arg(&template)
+            .assert()
+            .success();
+
+        let real_git = String::from_utf8(
+            StdCommand::new("sh")
+                .args(["-c", "command -v git"])
+                .output()
+                .unwrap()
+                .stdout,
+        )
+        .unwrap();
+        let stub_bin = td.path().join("bin");
+        fs::create_dir_all(&stub_bin).unwrap();
+        common::write_executable(
+            &stub_bin,
+            "git",
+            &format!(
+                r#"#!/bin/sh
+if [ "$1" = "worktree" ] && [ "$2" = "remove" ]; then
+  echo "fatal: simulated failure" >&2
+  exit 128
+fi
+exec "{real}" "$@"
+"#,
+                real = real_git.trim()
+            ),
+        );
+
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .env("PATH", common::prepend_path(&stub_bin))
+            .args(["rm", "agent-a", "--base-dir"])
+            .arg(&agents)
+            .assert()
+            .failure();
+
+        assert!(agents.join("agent-a").exists());
+        let list = StdCommand::new("git")
+            .current_dir(&repo)
+            .args(["worktree", "list", "--porcelain"])
+            .output()
+            .unwrap();
+        assert!(
+            String::from_utf8_lossy(&list.stdout).contains("locked nfs"),
+            "lock should be restored"
+        );
+        assert!(
+            agents
+                .join("agent-a")
+                .join(".devcontainer")
+                .join("devcontainer.json")
+                .exists(),
+            "generated files stay with the surviving agent"
+        );
+    }
 }
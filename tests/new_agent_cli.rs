@@ -1,4 +1,5 @@
 use std::fs;
+use std::process::Command as StdCommand;
 
 use assert_cmd::Command;
 use predicates::prelude::PredicateBooleanExt;
@@ -203,7 +204,7 @@ fn agent_new_detects_agent_name_collisions() {
             agents.to_str().unwrap(),
         ])
         .assert()
-        .failure()
+        .code(6)
         .stderr(contains("already exists").and(contains("different branch")));
 }
 
@@ -231,3 +232,1181 @@ fn agent_new_errors_when_derived_agent_name_is_too_long() {
         .failure()
         .stderr(contains("--agent-name"));
 }
+
+#[test]
+fn quiet_suppresses_informational_output() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+
+    let agents = td.path().join("agents");
+    fs::create_dir_all(&agents).unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args([
+            "--quiet",
+            "new",
+            "feat/a",
+            "--no-open",
+            "--base-dir",
+            agents.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout("");
+
+    assert!(agents.join("feat_a").exists());
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["rm", "feat/a", "-q", "--base-dir", agents.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn agent_new_verbose_reports_check_timings() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let agents = td.path().join("agents");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["-v", "new", "feat/a", "--no-open", "--base-dir"])
+        .arg(&agents)
+        .assert()
+        .success()
+        .stderr(
+            contains("Worktree collision checks: ").and(contains("Base and branch ref checks: ")),
+        );
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "feat/b", "--no-open", "--base-dir"])
+        .arg(&agents)
+        .assert()
+        .success()
+        .stderr(contains("checks:").not());
+}
+
+#[test]
+fn agent_new_rejects_base_dir_inside_repo() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+
+    let agents = repo.join("agents");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args([
+            "new",
+            "feat/a",
+            "--no-open",
+            "--base-dir",
+            agents.to_str().unwrap(),
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("must be outside the repository"));
+
+    assert!(!agents.exists(), "base dir should not be left behind");
+}
+
+#[test]
+fn agent_new_expands_tilde_in_base_dir() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let home = td.path().join("home");
+    fs::create_dir_all(&home).unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .env("HOME", &home)
+        .args(["new", "feat/a", "--no-open", "--base-dir", "~/work/agents"])
+        .assert()
+        .success();
+
+    assert!(home.join("work/agents/feat_a").is_dir());
+    assert!(!repo.join("~").exists());
+}
+
+#[test]
+fn agent_new_template_dir_copies_into_devcontainer() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+
+    let agents = td.path().join("agents");
+    fs::create_dir_all(&agents).unwrap();
+
+    let template = td.path().join("tpl");
+    fs::create_dir_all(template.join("scripts")).unwrap();
+    fs::write(template.join("devcontainer.json"), "{}\n").unwrap();
+    fs::write(template.join("scripts").join("setup.sh"), "echo hi\n").unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args([
+            "new",
+            "feat/a",
+            "--no-open",
+            "--base-dir",
+            agents.to_str().unwrap(),
+            "--template-dir",
+            template.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Devcontainer: 2 file(s)"));
+
+    let dc = agents.join("feat_a").join(".devcontainer");
+    assert_eq!(
+        fs::read_to_string(dc.join("devcontainer.json")).unwrap(),
+        "{}\n"
+    );
+    assert!(dc.join("scripts").join("setup.sh").exists());
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["rm", "feat/a", "--base-dir", agents.to_str().unwrap()])
+        .assert()
+        .success();
+    assert!(!agents.join("feat_a").exists());
+}
+
+#[test]
+fn agent_new_sparse_checks_out_only_the_cone() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    fs::create_dir_all(repo.join("app")).unwrap();
+    fs::create_dir_all(repo.join("docs")).unwrap();
+    fs::write(repo.join("app").join("main.rs"), "fn main() {}\n").unwrap();
+    fs::write(repo.join("docs").join("guide.md"), "# guide\n").unwrap();
+    common::run_git(&repo, &["add", "-A"]);
+    common::run_git(
+        &repo,
+        &[
+            "-c",
+            "user.name=pc-test",
+            "-c",
+            "user.email=pc-test@example.com",
+            "commit",
+            "-qm",
+            "dirs",
+        ],
+    );
+
+    let agents = td.path().join("agents");
+    let template = td.path().join("tpl");
+    fs::create_dir_all(&template).unwrap();
+    fs::write(template.join("devcontainer.json"), "{}\n").unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args([
+            "new",
+            "feat/a",
+            "--no-open",
+            "--sparse",
+            "app",
+            "--base-dir",
+            agents.to_str().unwrap(),
+            "--template-dir",
+            template.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    let wt = agents.join("feat_a");
+    assert!(wt.join("app").join("main.rs").exists());
+    assert!(wt.join("README.md").exists(), "root files stay in the cone");
+    assert!(!wt.join("docs").exists());
+    assert!(wt.join(".devcontainer").join("devcontainer.json").exists());
+    assert!(
+        repo.join("docs").join("guide.md").exists(),
+        "main checkout untouched"
+    );
+
+    let meta: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(common::git_path(&repo, "pc/agents/feat_a.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(meta["sparse_paths"], serde_json::json!(["app"]));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["rm", "feat/a", "--base-dir", agents.to_str().unwrap()])
+        .assert()
+        .success();
+    assert!(!wt.exists());
+}
+
+#[test]
+fn agent_new_env_from_base_copies_committed_env_example() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    // Predates `.env.example`; an existing branch is checked out as is, whatever the base.
+    common::run_git(&repo, &["branch", "old"]);
+    fs::write(repo.join(".env.example"), "PORT=3000\n").unwrap();
+    fs::write(repo.join(".gitignore"), ".env\n").unwrap();
+    common::run_git(&repo, &["add", "-A"]);
+    common::run_git(
+        &repo,
+        &[
+            "-c",
+            "user.name=pc-test",
+            "-c",
+            "user.email=pc-test@example.com",
+            "commit",
+            "-qm",
+            "env example",
+        ],
+    );
+    // The working tree copy differs; the committed one must win.
+    fs::write(repo.join(".env.example"), "PORT=9999\n").unwrap();
+    let agents = td.path().join("agents");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args([
+            "new",
+            "feat/a",
+            "--no-open",
+            "--env-from-base",
+            "--base-dir",
+        ])
+        .arg(&agents)
+        .assert()
+        .success()
+        .stdout(contains("Wrote .env from feat/a:.env.example"));
+
+    let env = agents.join("feat_a").join(".env");
+    assert_eq!(fs::read_to_string(&env).unwrap(), "PORT=3000\n");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["rm", "feat/a", "--base-dir"])
+        .arg(&agents)
+        .assert()
+        .success();
+    assert!(!agents.join("feat_a").exists());
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args([
+            "new",
+            "old",
+            "--no-open",
+            "--env-from-base",
+            "--base",
+            "main",
+        ])
+        .arg("--base-dir")
+        .arg(&agents)
+        .assert()
+        .success()
+        .stderr(contains("old:.env.example not found"));
+    assert!(!agents.join("old").join(".env").exists());
+}
+
+#[test]
+fn agent_new_companion_adds_and_removes_sibling_worktree() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let frontend = td.path().join("frontend");
+    common::init_repo(&frontend);
+    common::run_git(&frontend, &["branch", "feat/ui"]);
+    let agents = td.path().join("agents");
+    let companion = format!("{}#feat/ui", frontend.display());
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "feat/a", "--no-open", "--companion", &companion])
+        .arg("--base-dir")
+        .arg(&agents)
+        .assert()
+        .success();
+
+    let wt = agents.join("feat_a");
+    let companion_wt = wt.join(".pc-companions").join("frontend");
+    assert!(companion_wt.join("README.md").exists());
+    let status = StdCommand::new("git")
+        .current_dir(&wt)
+        .args(["status", "--porcelain"])
+        .output()
+        .unwrap();
+    assert!(status.stdout.is_empty(), "companions are excluded");
+    let meta: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(common::git_path(&repo, "pc/agents/feat_a.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(meta["companions"][0]["branch"], "feat/ui");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["rm", "feat/a", "--base-dir"])
+        .arg(&agents)
+        .assert()
+        .success();
+    assert!(!wt.exists());
+    let list = StdCommand::new("git")
+        .current_dir(&frontend)
+        .args(["worktree", "list", "--porcelain"])
+        .output()
+        .unwrap();
+    let list = String::from_utf8(list.stdout).unwrap();
+    assert_eq!(list.matches("worktree ").count(), 1, "{list}");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "feat/c", "--no-open", "--companion", &companion])
+        .arg("--base-dir")
+        .arg(&agents)
+        .assert()
+        .success();
+    let wt_c = agents.join("feat_c");
+    let companion_c = wt_c.join(".pc-companions").join("frontend");
+    fs::write(companion_c.join("README.md"), "edited\n").unwrap();
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["rm", "feat/c", "--base-dir"])
+        .arg(&agents)
+        .assert()
+        .code(7);
+    assert_eq!(
+        fs::read_to_string(companion_c.join("README.md")).unwrap(),
+        "edited\n",
+        "a dirty companion is kept along with the agent"
+    );
+    assert!(wt_c.join("README.md").exists());
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["rm", "feat/c", "--force", "--base-dir"])
+        .arg(&agents)
+        .assert()
+        .success();
+    assert!(!wt_c.exists());
+
+    let missing = format!("{}#nope", frontend.display());
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "feat/b", "--no-open", "--companion", &missing])
+        .arg("--base-dir")
+        .arg(&agents)
+        .assert()
+        .code(3)
+        .stderr(contains("Companion branch not found: nope"));
+    assert!(!agents.join("feat_b").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn agent_new_template_dir_keeps_executable_bit() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let agents = td.path().join("agents");
+
+    let template = td.path().join("tpl");
+    fs::create_dir_all(&template).unwrap();
+    fs::write(template.join("devcontainer.json"), "{}\n").unwrap();
+    common::write_executable(&template, "post-create.sh", "#!/bin/sh\necho ready\n");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "feat/a", "--no-open", "--template-dir"])
+        .arg(&template)
+        .arg("--base-dir")
+        .arg(&agents)
+        .assert()
+        .success();
+
+    let script = agents
+        .join("feat_a")
+        .join(".devcontainer")
+        .join("post-create.sh");
+    let mode = fs::metadata(&script).unwrap().permissions().mode();
+    assert_eq!(mode & 0o111, 0o111, "mode {mode:o}");
+}
+
+#[test]
+fn agent_new_attach_adopts_existing_worktree() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let manual = td.path().join("manual-wt");
+    common::run_git(
+        &repo,
+        &[
+            "worktree",
+            "add",
+            "-q",
+            "-b",
+            "feat/a",
+            manual.to_str().unwrap(),
+        ],
+    );
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args([
+            "new",
+            "feat/a",
+            "--attach",
+            "--no-open",
+            "--label",
+            "team=core",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Attached existing worktree as agent feat_a"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["path", "feat_a"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}\n",
+            fs::canonicalize(&manual).unwrap().display()
+        ));
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["list", "--label", "team=core"])
+        .assert()
+        .success()
+        .stdout(contains("feat_a"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "feat/none", "--attach", "--no-open"])
+        .assert()
+        .code(3)
+        .stderr(contains("No worktree to attach for branch feat/none"));
+
+    let main_branch = StdCommand::new("git")
+        .current_dir(&repo)
+        .args(["branch", "--show-current"])
+        .output()
+        .unwrap();
+    let main_branch = String::from_utf8(main_branch.stdout).unwrap();
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", main_branch.trim(), "--attach", "--no-open"])
+        .assert()
+        .code(2)
+        .stderr(contains("Cannot attach the main worktree"));
+    assert!(repo.join("README.md").exists());
+}
+
+#[test]
+fn agent_new_template_dir_requires_devcontainer_json() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+
+    let agents = td.path().join("agents");
+    fs::create_dir_all(&agents).unwrap();
+
+    let template = td.path().join("tpl");
+    fs::create_dir_all(&template).unwrap();
+    fs::write(template.join("Dockerfile"), "FROM scratch\n").unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args([
+            "new",
+            "feat/a",
+            "--no-open",
+            "--base-dir",
+            agents.to_str().unwrap(),
+            "--template-dir",
+            template.to_str().unwrap(),
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("must contain devcontainer.json"));
+
+    assert!(!agents.join("feat_a").exists());
+}
+
+#[test]
+fn failures_map_to_documented_exit_codes() {
+    let td = TempDir::new().unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(contains("Exit codes:"));
+
+    let not_repo = td.path().join("plain");
+    fs::create_dir_all(&not_repo).unwrap();
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&not_repo)
+        .env("GIT_CEILING_DIRECTORIES", td.path())
+        .args(["new", "feat/a", "--no-open"])
+        .assert()
+        .code(3)
+        .stderr(contains("Not a git repository").or(contains("no commits")));
+
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "bad branch", "--no-open"])
+        .assert()
+        .code(5)
+        .stderr(contains("Invalid branch name"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "feat/a", "--no-open", "--base", "does-not-exist"])
+        .assert()
+        .code(3)
+        .stderr(contains("Base ref not found"));
+
+    let empty_path = td.path().join("empty-bin");
+    fs::create_dir_all(&empty_path).unwrap();
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .env("PATH", &empty_path)
+        .args(["new", "feat/a", "--no-open"])
+        .assert()
+        .code(4)
+        .stderr(contains("git not found in PATH"));
+}
+
+#[test]
+fn agent_new_reads_defaults_from_repo_pc_toml() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+
+    fs::create_dir_all(repo.join("tooling").join("devcontainer")).unwrap();
+    fs::write(
+        repo.join("tooling")
+            .join("devcontainer")
+            .join("devcontainer.json"),
+        "{}\n",
+    )
+    .unwrap();
+    fs::write(
+        repo.join(".pc.toml"),
+        "base_dir = \"../shared-agents\"\ntemplate_dir = \"tooling/devcontainer\"\n",
+    )
+    .unwrap();
+    common::run_git(&repo, &["add", "-A"]);
+    common::run_git(
+        &repo,
+        &[
+            "-c",
+            "user.name=pc-test",
+            "-c",
+            "user.email=pc-test@example.com",
+            "commit",
+            "-m",
+            "pc config",
+        ],
+    );
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(repo.join("tooling"))
+        .env_remove("AGENT_WORKTREE_BASE_DIR")
+        .args(["new", "feat/a", "--no-open"])
+        .assert()
+        .success();
+
+    let worktree = td.path().join("shared-agents").join("feat_a");
+    assert!(worktree
+        .join(".devcontainer")
+        .join("devcontainer.json")
+        .exists());
+
+    let flag_dir = td.path().join("flag-agents");
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .env_remove("AGENT_WORKTREE_BASE_DIR")
+        .args([
+            "new",
+            "feat/b",
+            "--no-open",
+            "--base-dir",
+            flag_dir.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+    assert!(flag_dir.join("feat_b").exists());
+}
+
+#[test]
+fn agent_new_uses_worktree_path_template() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let agents = td.path().join("agents");
+    fs::write(
+        repo.join(".pc.toml"),
+        "worktree_path_template = \"{repo}/{user}/{agent}\"\n",
+    )
+    .unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .env("USER", "tester")
+        .args(["new", "feat/a", "--no-open", "--base-dir"])
+        .arg(&agents)
+        .assert()
+        .success();
+
+    let worktree = agents.join("repo").join("tester").join("feat_a");
+    assert!(worktree.join("README.md").exists());
+    let meta: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(common::git_path(&repo, "pc/agents/feat_a.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        meta["worktree_path"],
+        fs::canonicalize(&worktree).unwrap().to_str().unwrap()
+    );
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["rm", "feat/a", "--base-dir"])
+        .arg(&agents)
+        .assert()
+        .success();
+    assert!(
+        !agents.join("repo").exists(),
+        "empty template dirs are removed"
+    );
+    assert!(agents.exists());
+
+    fs::write(
+        repo.join(".pc.toml"),
+        "worktree_path_template = \"../{agent}\"\n",
+    )
+    .unwrap();
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "feat/b", "--no-open", "--base-dir"])
+        .arg(&agents)
+        .assert()
+        .code(2)
+        .stderr(contains("Invalid worktree_path_template"));
+}
+
+#[test]
+fn agent_new_rejects_unknown_pc_toml_keys() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    fs::write(repo.join(".pc.toml"), "base_dirr = \"x\"\n").unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "feat/a", "--no-open"])
+        .assert()
+        .failure()
+        .stderr(contains(".pc.toml"));
+}
+
+#[test]
+fn agent_new_preflight_enforces_max_agents() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    fs::write(repo.join(".pc.toml"), "max_agents = 1\n").unwrap();
+
+    let agents = td.path().join("agents");
+    let new = |branch: &str, extra: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pc"));
+        cmd.current_dir(&repo)
+            .args(["new", branch, "--no-open", "--base-dir"])
+            .arg(&agents)
+            .args(extra);
+        cmd
+    };
+
+    new("feat/a", &[]).assert().success();
+    new("feat/b", &[])
+        .assert()
+        .code(3)
+        .stderr(contains("Agent limit reached"));
+    assert!(!agents.join("feat_b").exists());
+
+    new("feat/b", &["--no-preflight"]).assert().success();
+}
+
+#[test]
+fn labels_round_trip_through_agent_metadata() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let agents = td.path().join("agents");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args([
+            "new",
+            "feat/a",
+            "--no-open",
+            "--base-dir",
+            agents.to_str().unwrap(),
+            "--label",
+            "team=core",
+            "--label",
+            "ticket=PC-42=b",
+        ])
+        .assert()
+        .success();
+
+    let meta: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(common::git_path(&repo, "pc/agents/feat_a.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        meta["labels"],
+        serde_json::json!({ "team": "core", "ticket": "PC-42=b" })
+    );
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "feat/b", "--no-open", "--label", "missing-value"])
+        .assert()
+        .code(2)
+        .stderr(contains("KEY=VALUE"));
+}
+
+#[test]
+fn pc_lang_selects_translated_summary_lines() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let agents = td.path().join("agents");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .env("PC_LANG", "zh-CN")
+        .args([
+            "new",
+            "feat/a",
+            "--no-open",
+            "--base-dir",
+            agents.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout(contains("工作树：").and(contains("分支：")));
+}
+
+#[test]
+fn save_profile_then_reuse_it() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    common::run_git(&repo, &["branch", "develop"]);
+    fs::write(repo.join(".pc.toml"), "# team defaults\nmax_agents = 10\n").unwrap();
+    let agents = td.path().join("agents");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args([
+            "new",
+            "feat/a",
+            "--no-open",
+            "--base-dir",
+            agents.to_str().unwrap(),
+            "--base",
+            "develop",
+            "--label",
+            "team=core",
+            "--save-profile",
+            "backend",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Saved agent profile backend"));
+
+    let config = fs::read_to_string(repo.join(".pc.toml")).unwrap();
+    assert!(config.starts_with("# team defaults\nmax_agents = 10\n"));
+    assert!(config.contains("[agent_profile.backend]"));
+    assert!(config.contains("base = \"develop\""));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args([
+            "new",
+            "feat/b",
+            "--no-open",
+            "--base-dir",
+            agents.to_str().unwrap(),
+            "--profile",
+            "backend",
+            "--label",
+            "ticket=42",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("uncommitted change").not());
+
+    let meta: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(common::git_path(&repo, "pc/agents/feat_b.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(meta["profile"], "backend");
+    assert_eq!(
+        meta["labels"],
+        serde_json::json!({ "team": "core", "ticket": "42" })
+    );
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "feat/c", "--no-open", "--profile", "nope"])
+        .assert()
+        .code(2)
+        .stderr(contains("Unknown agent profile: nope"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "feat/d", "--no-open", "--base", "nope"])
+        .args(["--save-profile", "broken"])
+        .assert()
+        .failure();
+    let config = fs::read_to_string(repo.join(".pc.toml")).unwrap();
+    assert!(!config.contains("broken"), "{config}");
+}
+
+#[test]
+fn fetch_makes_new_remote_base_available_and_tracks_it() {
+    let td = TempDir::new().unwrap();
+    let origin = td.path().join("origin");
+    common::init_repo(&origin);
+    let repo = td.path().join("repo");
+    common::run_git(
+        td.path(),
+        &["clone", "--quiet", origin.to_str().unwrap(), "repo"],
+    );
+
+    common::run_git(&origin, &["checkout", "--quiet", "-b", "feature-x"]);
+    fs::write(origin.join("x.txt"), "x\n").unwrap();
+    common::run_git(&origin, &["add", "-A"]);
+    common::run_git(
+        &origin,
+        &[
+            "-c",
+            "user.name=pc-test",
+            "-c",
+            "user.email=pc-test@example.com",
+            "commit",
+            "-m",
+            "x",
+        ],
+    );
+
+    let agents = td.path().join("agents");
+    let new_args = [
+        "new",
+        "feat/a",
+        "--no-open",
+        "--base",
+        "origin/feature-x",
+        "--base-dir",
+        agents.to_str().unwrap(),
+    ];
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(new_args)
+        .assert()
+        .code(3)
+        .stderr(contains("Base ref not found: origin/feature-x"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(new_args)
+        .arg("--fetch")
+        .assert()
+        .success();
+    assert!(agents.join("feat_a/x.txt").is_file());
+
+    let upstream = std::process::Command::new("git")
+        .current_dir(&repo)
+        .args(["rev-parse", "--abbrev-ref", "feat/a@{upstream}"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&upstream.stdout).trim(),
+        "origin/feature-x"
+    );
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args([
+            "new",
+            "feat/b",
+            "--no-open",
+            "--base",
+            "main",
+            "--base-dir",
+            agents.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+    let upstream = std::process::Command::new("git")
+        .current_dir(&repo)
+        .args(["rev-parse", "--abbrev-ref", "feat/b@{upstream}"])
+        .output()
+        .unwrap();
+    assert!(!upstream.status.success());
+}
+
+#[test]
+fn count_creates_numbered_agents_and_checks_collisions_first() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let agents = td.path().join("agents");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "exp", "--count", "3", "--no-open", "--base-dir"])
+        .arg(&agents)
+        .assert()
+        .success()
+        .stdout(contains("Created 3 agents: exp-1, exp-2, exp-3"));
+    for i in 1..=3 {
+        assert!(agents.join(format!("exp-{i}")).is_dir());
+    }
+
+    common::run_git(&repo, &["branch", "try-2"]);
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "try", "--count", "2", "--no-open", "--base-dir"])
+        .arg(&agents)
+        .assert()
+        .code(3)
+        .stderr(contains("Branch already exists: try-2"));
+    assert!(!agents.join("try-1").exists());
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "exp", "--count", "0", "--no-open"])
+        .assert()
+        .code(2);
+
+    // A companion branch can only be checked out once, so it cannot be shared.
+    let companion = td.path().join("frontend");
+    common::init_repo(&companion);
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "web", "--count", "2", "--no-open", "--companion"])
+        .arg(format!("{}#main", companion.display()))
+        .assert()
+        .code(2)
+        .stderr(contains("--companion"));
+}
+
+#[test]
+fn push_upstream_records_upstream_and_failures_do_not_roll_back() {
+    let td = TempDir::new().unwrap();
+    let origin = td.path().join("origin");
+    common::init_repo(&origin);
+    let repo = td.path().join("repo");
+    common::run_git(
+        td.path(),
+        &["clone", "--quiet", origin.to_str().unwrap(), "repo"],
+    );
+    let agents = td.path().join("agents");
+    let pc_new = |extra: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pc"));
+        cmd.current_dir(&repo)
+            .args(["new", "--no-open", "--base-dir"])
+            .arg(&agents)
+            .args(extra);
+        cmd
+    };
+    let read_meta = |agent: &str| -> serde_json::Value {
+        serde_json::from_str(
+            &fs::read_to_string(common::git_path(&repo, &format!("pc/agents/{agent}.json")))
+                .unwrap(),
+        )
+        .unwrap()
+    };
+
+    pc_new(&["feat/a", "--push-upstream", "origin"])
+        .assert()
+        .success()
+        .stdout(contains("Upstream: origin/feat/a"));
+    assert_eq!(read_meta("feat_a")["upstream"], "origin/feat/a");
+    let pushed = std::process::Command::new("git")
+        .current_dir(&origin)
+        .args(["show-ref", "--verify", "--quiet", "refs/heads/feat/a"])
+        .status()
+        .unwrap();
+    assert!(pushed.success());
+
+    pc_new(&["feat/b", "--push-upstream", "no-such-remote"])
+        .assert()
+        .success()
+        .stderr(contains("Warning: failed to push feat/b to no-such-remote"));
+    assert!(agents.join("feat_b").is_dir());
+    assert!(read_meta("feat_b").get("upstream").is_none());
+
+    pc_new(&["feat/c", "--base", "origin/main"])
+        .assert()
+        .success();
+    assert_eq!(read_meta("feat_c")["upstream"], "origin/main");
+
+    pc_new(&["feat/d", "--base", "origin/main", "--no-branch-track"])
+        .assert()
+        .success();
+    assert!(read_meta("feat_d").get("upstream").is_none());
+}
+
+#[test]
+fn agent_new_from_tag_or_sha_prints_resolved_commit() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let rev_parse = |rev: &str| -> String {
+        let out = std::process::Command::new("git")
+            .current_dir(&repo)
+            .args(["rev-parse", rev])
+            .output()
+            .unwrap();
+        String::from_utf8(out.stdout).unwrap().trim().to_string()
+    };
+    let first = rev_parse("HEAD");
+    common::run_git(&repo, &["tag", "v1"]);
+    common::run_git(
+        &repo,
+        &[
+            "-c",
+            "user.name=pc-test",
+            "-c",
+            "user.email=pc-test@example.com",
+            "commit",
+            "--allow-empty",
+            "-qm",
+            "second",
+        ],
+    );
+    let agents = td.path().join("agents");
+    let pc_new = |extra: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pc"));
+        cmd.current_dir(&repo)
+            .args(["new", "--no-open", "--base-dir"])
+            .arg(&agents)
+            .args(extra);
+        cmd
+    };
+
+    pc_new(&["feat/tag", "--base", "v1"])
+        .assert()
+        .success()
+        .stdout(contains(format!("Base: v1 (tag) -> {first}")))
+        .stderr(contains(format!("Creating it from v1 (tag {first})")));
+    assert_eq!(rev_parse("feat/tag"), first);
+
+    pc_new(&["feat/sha", "--base", &first[..10]])
+        .assert()
+        .success()
+        .stdout(contains(format!("(commit) -> {first}")));
+    assert_eq!(rev_parse("feat/sha"), first);
+
+    // A branch named like the tag makes `--base v1` ambiguous until qualified.
+    common::run_git(&repo, &["branch", "v1", "HEAD"]);
+    pc_new(&["feat/amb", "--base", "v1"])
+        .assert()
+        .code(2)
+        .stderr(contains("Base ref is ambiguous: v1"));
+    assert!(!agents.join("feat_amb").exists());
+    pc_new(&["feat/amb", "--base", "refs/tags/v1"])
+        .assert()
+        .success();
+    assert_eq!(rev_parse("feat/amb"), first);
+}
+
+#[cfg(unix)]
+#[test]
+fn agent_new_no_devcontainer_skips_configured_template() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let template = repo.join("tooling").join("devcontainer");
+    fs::create_dir_all(&template).unwrap();
+    fs::write(template.join("devcontainer.json"), "{}\n").unwrap();
+    fs::write(
+        repo.join(".pc.toml"),
+        "template_dir = \"tooling/devcontainer\"\n",
+    )
+    .unwrap();
+    common::run_git(&repo, &["add", "-A"]);
+    common::run_git(
+        &repo,
+        &[
+            "-c",
+            "user.name=pc-test",
+            "-c",
+            "user.email=pc-test@example.com",
+            "commit",
+            "-qm",
+            "pc config",
+        ],
+    );
+
+    // Any devcontainer/docker invocation would leave a trace in the log.
+    let stub_bin = td.path().join("bin");
+    fs::create_dir_all(&stub_bin).unwrap();
+    let log = td.path().join("calls.log");
+    for tool in ["devcontainer", "docker"] {
+        common::write_executable(
+            &stub_bin,
+            tool,
+            &format!("#!/bin/sh\necho {tool} \"$@\" >> '{}'\n", log.display()),
+        );
+    }
+    let agents = td.path().join("agents");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .env("PATH", common::prepend_path(&stub_bin))
+        .args([
+            "new",
+            "feat/a",
+            "--no-open",
+            "--no-devcontainer",
+            "--base-dir",
+        ])
+        .arg(&agents)
+        .assert()
+        .success();
+
+    let worktree = agents.join("feat_a");
+    assert!(worktree.join("README.md").is_file());
+    assert!(!worktree.join(".devcontainer").exists());
+    assert!(!log.exists());
+    let meta: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(common::git_path(&repo, "pc/agents/feat_a.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(meta["generated_files"], serde_json::json!([]));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "feat/b", "--no-devcontainer", "--template-dir"])
+        .arg(&template)
+        .assert()
+        .code(2);
+}
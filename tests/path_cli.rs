@@ -0,0 +1,87 @@
+use std::fs;
+
+use assert_cmd::Command;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+
+#[test]
+fn path_prints_only_the_worktree_path() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let agents = td.path().join("agents");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["new", "feat/a", "--no-open", "--base-dir"])
+        .arg(&agents)
+        .assert()
+        .success();
+    let expected = format!(
+        "{}\n",
+        fs::canonicalize(agents.join("feat_a")).unwrap().display()
+    );
+
+    for arg in ["feat/a", "feat_a"] {
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .env_remove("AGENT_WORKTREE_BASE_DIR")
+            .args(["path", arg])
+            .assert()
+            .success()
+            .stdout(expected.clone());
+    }
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+        .current_dir(&repo)
+        .args(["path", "nope"])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(contains("Agent worktree not found"));
+}
+
+#[cfg(unix)]
+#[test]
+fn bash_shell_init_defines_pca() {
+    let td = TempDir::new().unwrap();
+    let repo = td.path().join("repo");
+    common::init_repo(&repo);
+    let agents = td.path().join("agents");
+
+    let pc = assert_cmd::cargo::cargo_bin!("pc");
+    Command::new(pc)
+        .current_dir(&repo)
+        .args(["new", "feat/a", "--no-open", "--base-dir"])
+        .arg(&agents)
+        .assert()
+        .success();
+
+    let has_bash = std::process::Command::new("bash")
+        .arg("--version")
+        .output()
+        .is_ok();
+    if !has_bash {
+        return;
+    }
+    let out = std::process::Command::new("bash")
+        .current_dir(&repo)
+        .env("PATH", common::prepend_path(pc.parent().unwrap()))
+        .args([
+            "-c",
+            "eval \"$(pc shell-init bash)\" && pca feat/a && pwd -P",
+        ])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout).trim(),
+        fs::canonicalize(agents.join("feat_a"))
+            .unwrap()
+            .display()
+            .to_string()
+    );
+}
@@ -0,0 +1,132 @@
+#[cfg(unix)]
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(unix)]
+mod unix_only {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::path::Path;
+    use std::process::Command as StdCommand;
+    use std::time::{Duration, Instant};
+
+    use assert_cmd::Command;
+    use tempfile::TempDir;
+
+    use super::common;
+
+    fn get(socket: &Path, request: &str) -> String {
+        let mut stream = UnixStream::connect(socket).unwrap();
+        stream
+            .write_all(format!("{request} HTTP/1.1\r\nHost: pc\r\n\r\n").as_bytes())
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn serve_answers_json_over_unix_socket_and_stops_on_sigterm() {
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+        let agents = td.path().join("agents");
+        Command::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["new", "feat/a", "--no-open", "--label", "team=core"])
+            .arg("--base-dir")
+            .arg(&agents)
+            .assert()
+            .success();
+
+        let socket = td.path().join("pc.sock");
+        let mut child = StdCommand::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .arg("serve")
+            .arg("--socket")
+            .arg(&socket)
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        let deadline = Instant::now() + Duration::from_secs(20);
+        while UnixStream::connect(&socket).is_err() {
+            assert!(Instant::now() < deadline, "server did not start");
+            std::thread::sleep(Duration::from_millis(50));
+        }
+
+        // A client that never sends its request must not hold up the others.
+        let _stalled = UnixStream::connect(&socket).unwrap();
+        let started = Instant::now();
+        let health = get(&socket, "GET /health");
+        assert!(
+            started.elapsed() < Duration::from_secs(3),
+            "request waited for a stalled connection"
+        );
+        assert!(health.starts_with("HTTP/1.1 200 OK"), "{health}");
+        assert!(health.contains(r#""status":"ok""#), "{health}");
+
+        let list = get(&socket, "GET /agents");
+        assert!(list.contains(r#""agent_name":"feat_a""#), "{list}");
+        assert!(list.contains(r#""branch_exists":true"#), "{list}");
+
+        let one = get(&socket, "GET /agents/feat_a");
+        assert!(one.contains(r#""team":"core""#), "{one}");
+        assert!(get(&socket, "GET /agents/nope").starts_with("HTTP/1.1 404"));
+        // A request line without an end is cut off at the size limit, not waited for.
+        let mut flood = UnixStream::connect(&socket).unwrap();
+        let started = Instant::now();
+        flood.write_all(&[b'A'; 8 * 1024]).unwrap();
+        let mut response = String::new();
+        flood.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 400"), "{response}");
+        assert!(started.elapsed() < Duration::from_secs(3));
+        assert!(get(&socket, "DELETE /agents/feat_a").starts_with("HTTP/1.1 405"));
+        assert!(agents.join("feat_a").is_dir());
+
+        let status = StdCommand::new("kill")
+            .args(["-TERM", &child.id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let exit = child.wait().unwrap();
+        assert!(exit.success(), "{exit:?}");
+        assert!(!socket.exists());
+    }
+
+    #[test]
+    fn serve_quiet_on_port_zero_reports_the_bound_address() {
+        use std::io::BufRead;
+        use std::net::TcpStream;
+
+        let td = TempDir::new().unwrap();
+        let repo = td.path().join("repo");
+        common::init_repo(&repo);
+
+        let mut child = StdCommand::new(assert_cmd::cargo::cargo_bin!("pc"))
+            .current_dir(&repo)
+            .args(["-q", "serve", "--port", "0"])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        std::io::BufReader::new(child.stderr.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let addr = line
+            .trim()
+            .strip_prefix("Listening on http://")
+            .unwrap_or_else(|| panic!("unexpected first line: {line}"))
+            .to_string();
+
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .write_all(b"GET /health HTTP/1.1\r\nHost: pc\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let _ = child.kill();
+        let _ = child.wait();
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+    }
+}